@@ -1,7 +1,11 @@
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::SeedableRng;
+use std::error::Error;
 use std::fmt::Display;
+use std::ops::{BitAnd, BitOr, Sub};
 use std::slice::Iter;
+use std::str::FromStr;
 
 #[macro_export]
 macro_rules! new_card {
@@ -26,6 +30,38 @@ impl Suit {
         static SUITS: [Suit; 4] = [Suit::Heart, Suit::Diamond, Suit::Spade, Suit::Club];
         SUITS.iter()
     }
+
+    /// The suit's letter in standard card notation
+    pub fn to_char(&self) -> char {
+        match self {
+            Suit::Heart => 'h',
+            Suit::Diamond => 'd',
+            Suit::Spade => 's',
+            Suit::Club => 'c',
+        }
+    }
+
+    pub fn from_char(c: char) -> Result<Self, ParseCardError> {
+        match c.to_ascii_lowercase() {
+            'h' => Ok(Suit::Heart),
+            'd' => Ok(Suit::Diamond),
+            's' => Ok(Suit::Spade),
+            'c' => Ok(Suit::Club),
+            _ => Err(ParseCardError::InvalidSuit(c)),
+        }
+    }
+}
+
+impl FromStr for Suit {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Suit::from_char(c),
+            _ => Err(ParseCardError::InvalidLength(s.to_string())),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialOrd, PartialEq, Eq, Ord, Hash)]
@@ -65,6 +101,46 @@ impl Rank {
         ];
         RANKS.iter()
     }
+
+    /// The rank's character in standard card notation, with `T` for ten
+    pub fn to_char(&self) -> char {
+        match self {
+            Rank::Null => '?',
+            Rank::Two => '2',
+            Rank::Three => '3',
+            Rank::Four => '4',
+            Rank::Five => '5',
+            Rank::Six => '6',
+            Rank::Seven => '7',
+            Rank::Eight => '8',
+            Rank::Nine => '9',
+            Rank::Ten => 'T',
+            Rank::Jack => 'J',
+            Rank::Queen => 'Q',
+            Rank::King => 'K',
+            Rank::Ace => 'A',
+        }
+    }
+
+    pub fn from_char(c: char) -> Result<Self, ParseCardError> {
+        let c = c.to_ascii_uppercase();
+        Rank::iterator()
+            .find(|rank| rank.to_char() == c)
+            .copied()
+            .ok_or(ParseCardError::InvalidRank(c))
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Rank::from_char(c),
+            _ => Err(ParseCardError::InvalidLength(s.to_string())),
+        }
+    }
 }
 
 impl From<i64> for Rank {
@@ -74,69 +150,414 @@ impl From<i64> for Rank {
                 return *rank;
             }
         }
-        return Rank::Null;
+        Rank::Null
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Card {
     pub rank: Rank,
     pub suit: Suit,
 }
 
+impl Card {
+    /// A unique index in `0..52` for the card, grouped by suit
+    pub fn index(&self) -> usize {
+        (self.suit as usize) * 13 + self.rank as usize
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        Card {
+            rank: Rank::from((index % 13) as i64),
+            suit: *Suit::iterator().nth(index / 13).unwrap(),
+        }
+    }
+
+    /// The card in two character notation, such as `As`
+    pub fn notation(&self) -> String {
+        format!("{}{}", self.rank.to_char(), self.suit.to_char())
+    }
+}
+
 impl Display for Card {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?} of {:?}s", self.rank, self.suit)
     }
 }
 
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    /// Parses two character notation such as `As`, `Td` or `7c`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(rank), Some(suit), None) => Ok(Card {
+                rank: Rank::from_char(rank)?,
+                suit: Suit::from_char(suit)?,
+            }),
+            _ => Err(ParseCardError::InvalidLength(s.to_string())),
+        }
+    }
+}
+
+/// An error from parsing card notation
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseCardError {
+    InvalidRank(char),
+    InvalidSuit(char),
+    InvalidLength(String),
+}
+
+impl Display for ParseCardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseCardError::InvalidRank(c) => write!(f, "invalid rank '{}'", c),
+            ParseCardError::InvalidSuit(c) => write!(f, "invalid suit '{}'", c),
+            ParseCardError::InvalidLength(s) => write!(f, "invalid card notation \"{}\"", s),
+        }
+    }
+}
+
+impl Error for ParseCardError {}
+
+/// Parses a hand or board such as `AsKd` or `Qh Jh 2c`.
+///
+/// Cards may be written together or separated by spaces or commas.
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, ParseCardError> {
+    let chars: Vec<char> = s
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .collect();
+    if !chars.len().is_multiple_of(2) {
+        return Err(ParseCardError::InvalidLength(s.to_string()));
+    }
+    chars
+        .chunks(2)
+        .map(|pair| {
+            Ok(Card {
+                rank: Rank::from_char(pair[0])?,
+                suit: Suit::from_char(pair[1])?,
+            })
+        })
+        .collect()
+}
+
+/// Formats cards in compact notation separated by spaces, such as `Qh Jh 2c`
+pub fn format_cards(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|card| card.notation())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// A set of cards stored as a bitmask, with one bit per card given by `Card::index`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CardSet {
+    mask: u64,
+}
+
+impl CardSet {
+    const SUIT_MASK: u64 = (1 << 13) - 1;
+    const RANK_MASK: u64 = 1 | 1 << 13 | 1 << 26 | 1 << 39;
+
+    pub fn new() -> Self {
+        CardSet { mask: 0 }
+    }
+
+    /// All 52 cards
+    pub fn full() -> Self {
+        CardSet {
+            mask: (1 << 52) - 1,
+        }
+    }
+
+    pub fn from_mask(mask: u64) -> Self {
+        CardSet {
+            mask: mask & CardSet::full().mask,
+        }
+    }
+
+    pub fn mask(&self) -> u64 {
+        self.mask
+    }
+
+    /// All 13 cards of a suit
+    pub fn of_suit(suit: Suit) -> Self {
+        CardSet {
+            mask: CardSet::SUIT_MASK << (suit as usize * 13),
+        }
+    }
+
+    /// All 4 cards of a rank
+    pub fn of_rank(rank: Rank) -> Self {
+        CardSet {
+            mask: CardSet::RANK_MASK << rank as usize,
+        }
+    }
+
+    /// The ranks held in a suit, with bit `n` set for the rank with value `n`
+    pub fn suit_ranks(&self, suit: Suit) -> u16 {
+        ((self.mask >> (suit as usize * 13)) & CardSet::SUIT_MASK) as u16
+    }
+
+    /// The ranks held in any suit
+    pub fn ranks(&self) -> u16 {
+        Suit::iterator().fold(0, |acc, suit| acc | self.suit_ranks(*suit))
+    }
+
+    pub fn len(&self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    pub fn contains(&self, card: &Card) -> bool {
+        self.mask & (1 << card.index()) != 0
+    }
+
+    /// Adds a card, returning whether it was not already in the set
+    pub fn insert(&mut self, card: Card) -> bool {
+        let absent = !self.contains(&card);
+        self.mask |= 1 << card.index();
+        absent
+    }
+
+    /// Removes a card, returning whether it was in the set
+    pub fn remove(&mut self, card: &Card) -> bool {
+        let present = self.contains(card);
+        self.mask &= !(1 << card.index());
+        present
+    }
+
+    pub fn union(&self, other: &CardSet) -> CardSet {
+        CardSet {
+            mask: self.mask | other.mask,
+        }
+    }
+
+    pub fn intersection(&self, other: &CardSet) -> CardSet {
+        CardSet {
+            mask: self.mask & other.mask,
+        }
+    }
+
+    pub fn difference(&self, other: &CardSet) -> CardSet {
+        CardSet {
+            mask: self.mask & !other.mask,
+        }
+    }
+
+    pub fn is_disjoint(&self, other: &CardSet) -> bool {
+        self.mask & other.mask == 0
+    }
+
+    pub fn iter(&self) -> CardSetIter {
+        CardSetIter { mask: self.mask }
+    }
+
+    pub fn to_vec(&self) -> Vec<Card> {
+        self.iter().collect()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, other: CardSet) -> CardSet {
+        self.union(&other)
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, other: CardSet) -> CardSet {
+        self.intersection(&other)
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+
+    fn sub(self, other: CardSet) -> CardSet {
+        self.difference(&other)
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<T: IntoIterator<Item = Card>>(iter: T) -> Self {
+        let mut set = CardSet::new();
+        for card in iter {
+            set.insert(card);
+        }
+        set
+    }
+}
+
+impl From<&[Card]> for CardSet {
+    fn from(cards: &[Card]) -> Self {
+        cards.iter().copied().collect()
+    }
+}
+
+impl From<&Vec<Card>> for CardSet {
+    fn from(cards: &Vec<Card>) -> Self {
+        cards.iter().copied().collect()
+    }
+}
+
+impl From<CardSet> for Vec<Card> {
+    fn from(set: CardSet) -> Self {
+        set.to_vec()
+    }
+}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> CardSetIter {
+        self.iter()
+    }
+}
+
+/// Iterates over the cards of a `CardSet` in index order
+pub struct CardSetIter {
+    mask: u64,
+}
+
+impl Iterator for CardSetIter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.mask == 0 {
+            return None;
+        }
+        let index = self.mask.trailing_zeros() as usize;
+        self.mask &= self.mask - 1;
+        Some(Card::from_index(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.mask.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl Display for CardSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cards: Vec<String> = self.iter().map(|card| card.to_string()).collect();
+        write!(f, "[{}]", cards.join(", "))
+    }
+}
+
 #[derive(Debug)]
 pub struct Deck {
     cards: Vec<Card>,
     current_deal: usize,
+    rng: StdRng,
 }
 
 impl Deck {
+    /// A deck shuffled with a randomly seeded generator
     pub fn new() -> Self {
+        Deck::with_rng(StdRng::from_entropy())
+    }
+
+    /// A deck whose sequence of shuffles is fully determined by the seed
+    pub fn seeded(seed: u64) -> Self {
+        Deck::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    pub fn with_rng(rng: StdRng) -> Self {
         let mut new_deck = Deck {
             cards: vec![],
             current_deal: 0,
+            rng,
         };
+        new_deck.reset();
+        new_deck
+    }
+
+    /// Returns every removed card to the deck, leaving all 52 cards in order
+    pub fn reset(&mut self) {
+        self.cards.clear();
         for rank in Rank::iterator() {
             for suit in Suit::iterator() {
-                new_deck.cards.push(Card {
+                self.cards.push(Card {
                     rank: *rank,
                     suit: *suit,
                 });
             }
         }
-        new_deck
+        self.current_deal = 0;
     }
 
+    /// Shuffles every card that hasn't been removed back into the deck
     pub fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng);
+        self.cards.shuffle(&mut self.rng);
         self.current_deal = 0;
     }
 
-    pub fn get_next(&mut self) -> Card {
-        self.current_deal += 1;
-        self.cards[(self.current_deal - 1) % self.cards.len()]
+    /// Deals the next card, or `None` once the deck has run out
+    pub fn get_next(&mut self) -> Option<Card> {
+        let card = self.cards.get(self.current_deal).copied();
+        if card.is_some() {
+            self.current_deal += 1;
+        }
+        card
+    }
+
+    /// Deals the next card face down so that it takes no part in the hand
+    pub fn burn(&mut self) -> Option<Card> {
+        self.get_next()
+    }
+
+    /// The number of cards left to deal
+    pub fn remaining(&self) -> usize {
+        self.cards.len() - self.current_deal
+    }
+
+    /// Takes a known card out of the deck until the next `reset`.
+    ///
+    /// Returns false if the card has already been dealt or removed.
+    pub fn remove(&mut self, card: &Card) -> bool {
+        match self.cards[self.current_deal..]
+            .iter()
+            .position(|a| a == card)
+        {
+            Some(position) => {
+                self.cards.remove(self.current_deal + position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Takes every card in the set out of the deck until the next `reset`
+    pub fn remove_set(&mut self, cards: &CardSet) {
+        let current_deal = self.current_deal;
+        let mut idx = 0;
+        self.cards.retain(|card| {
+            idx += 1;
+            idx <= current_deal || !cards.contains(card)
+        });
+    }
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Display for Deck {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let res = write!(f, "[");
-        if res.is_err() {
-            return res;
-        }
-        for card in &self.cards[0..self.cards.len() - 1] {
-            let res = write!(f, "{}, ", card);
-            if res.is_err() {
-                return res;
-            }
-        }
-        write!(f, "{}]", self.cards[self.cards.len() - 1])
+        let cards: Vec<String> = self.cards.iter().map(|card| card.to_string()).collect();
+        write!(f, "[{}]", cards.join(", "))
     }
 }
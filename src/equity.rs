@@ -0,0 +1,562 @@
+use crate::card::{Card, CardSet, Deck};
+use crate::evaluator::evaluate_7;
+use crate::range::{Combo, Range};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::error::Error;
+use std::fmt::Display;
+
+const BOARD_SIZE: usize = 5;
+
+/// A hold'em spot to compute equity for: the known hole cards, a partial board and dead cards
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityQuery {
+    pub hands: Vec<(Card, Card)>,
+    /// Zero to five community cards already dealt
+    pub board: Vec<Card>,
+    /// Cards known to be out of the deck, such as mucked hands
+    pub dead: Vec<Card>,
+    /// Opponents with unknown hole cards, reported after the known hands
+    pub random_hands: usize,
+}
+
+impl EquityQuery {
+    pub fn new(hands: Vec<(Card, Card)>, board: Vec<Card>) -> Self {
+        EquityQuery {
+            hands,
+            board,
+            dead: vec![],
+            random_hands: 0,
+        }
+    }
+
+    pub fn with_dead(mut self, dead: Vec<Card>) -> Self {
+        self.dead = dead;
+        self
+    }
+
+    pub fn with_random_hands(mut self, random_hands: usize) -> Self {
+        self.random_hands = random_hands;
+        self
+    }
+
+    /// The number of players, known and random
+    pub fn players(&self) -> usize {
+        self.hands.len() + self.random_hands
+    }
+
+    /// Checks the spot can be dealt, returning the cards left in the deck
+    fn remaining_cards(&self) -> Result<CardSet, EquityError> {
+        if self.players() < 2 {
+            return Err(EquityError::TooFewHands);
+        }
+        if self.board.len() > BOARD_SIZE {
+            return Err(EquityError::TooManyBoardCards(self.board.len()));
+        }
+        let mut known = CardSet::new();
+        let hole_cards = self
+            .hands
+            .iter()
+            .flat_map(|(first, second)| [first, second]);
+        for card in hole_cards.chain(&self.board).chain(&self.dead) {
+            if !known.insert(*card) {
+                return Err(EquityError::DuplicateCard(*card));
+            }
+        }
+        let remaining = CardSet::full() - known;
+        if remaining.len() < 2 * self.random_hands + BOARD_SIZE - self.board.len() {
+            return Err(EquityError::NotEnoughCards);
+        }
+        Ok(remaining)
+    }
+}
+
+/// One player's results over every runout
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlayerEquity {
+    /// The fraction of runouts won outright
+    pub win: f64,
+    /// The fraction of runouts where the pot was split
+    pub tie: f64,
+    /// The expected share of the pot, a split between n players counting as 1/n
+    pub equity: f64,
+    /// The standard error of `equity`, zero when it was computed exactly
+    pub std_error: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityResult {
+    /// Each player's equity, in the order of the query's hands
+    pub players: Vec<PlayerEquity>,
+    /// The number of runouts evaluated
+    pub runouts: u64,
+}
+
+/// Why equity couldn't be computed for a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum EquityError {
+    TooFewHands,
+    TooManyBoardCards(usize),
+    /// A card is used more than once across the hands, board and dead cards
+    DuplicateCard(Card),
+    /// Too few cards are left in the deck to deal the random hands and complete the board
+    NotEnoughCards,
+    NoRunouts,
+    /// The ranges can't be dealt together, being empty or blocking each other
+    NoMatchups,
+}
+
+impl Display for EquityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EquityError::TooFewHands => write!(f, "equity needs at least two hands"),
+            EquityError::TooManyBoardCards(count) => {
+                write!(f, "a board has at most 5 cards, got {}", count)
+            }
+            EquityError::DuplicateCard(card) => write!(f, "{} is used more than once", card),
+            EquityError::NotEnoughCards => write!(f, "not enough cards left to deal"),
+            EquityError::NoRunouts => write!(f, "at least one runout is needed"),
+            EquityError::NoMatchups => {
+                write!(f, "the ranges have no combos that can be dealt together")
+            }
+        }
+    }
+}
+
+impl Error for EquityError {}
+
+/// Each player's accumulated results over the runouts seen so far
+struct Tally {
+    wins: Vec<f64>,
+    ties: Vec<f64>,
+    shares: Vec<f64>,
+    squared_shares: Vec<f64>,
+    total_weight: f64,
+    runouts: u64,
+}
+
+impl Tally {
+    fn new(players: usize) -> Self {
+        Tally {
+            wins: vec![0.0; players],
+            ties: vec![0.0; players],
+            shares: vec![0.0; players],
+            squared_shares: vec![0.0; players],
+            total_weight: 0.0,
+            runouts: 0,
+        }
+    }
+
+    /// Splits the pot of one complete board between the best hands, counting the runout
+    /// `weight` times, and returns each player's share
+    fn add(&mut self, hands: &[(Card, Card)], board: &[Card; BOARD_SIZE], weight: f64) -> Vec<f64> {
+        let shares = pot_shares(hands, board);
+        let winners = shares.iter().filter(|share| **share > 0.0).count();
+        for (player, share) in shares.iter().enumerate() {
+            if *share == 0.0 {
+                continue;
+            }
+            if winners == 1 {
+                self.wins[player] += weight;
+            } else {
+                self.ties[player] += weight;
+            }
+            self.shares[player] += weight * share;
+            self.squared_shares[player] += weight * share * share;
+        }
+        self.total_weight += weight;
+        self.runouts += 1;
+        shares
+    }
+
+    /// The results, with standard errors when the runouts were a random sample
+    fn result(&self, sampled: bool) -> EquityResult {
+        let total = self.total_weight;
+        let players = (0..self.wins.len())
+            .map(|player| {
+                let equity = self.shares[player] / total;
+                let variance = (self.squared_shares[player] / total - equity * equity).max(0.0);
+                PlayerEquity {
+                    win: self.wins[player] / total,
+                    tie: self.ties[player] / total,
+                    equity,
+                    std_error: if sampled {
+                        (variance / self.runouts as f64).sqrt()
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+        EquityResult {
+            players,
+            runouts: self.runouts,
+        }
+    }
+}
+
+/// Each hand's share of the pot on a complete board
+fn pot_shares(hands: &[(Card, Card)], board: &[Card; BOARD_SIZE]) -> Vec<f64> {
+    let values: Vec<u32> = hands
+        .iter()
+        .map(|(first, second)| {
+            evaluate_7(&[
+                *first, *second, board[0], board[1], board[2], board[3], board[4],
+            ])
+        })
+        .collect();
+    let best = *values.iter().max().unwrap();
+    let winners = values.iter().filter(|value| **value == best).count();
+    values
+        .iter()
+        .map(|value| {
+            if *value == best {
+                1.0 / winners as f64
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// How equity is computed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EquityMode {
+    /// Deals random runouts, from a seeded deck when `seed` is given
+    MonteCarlo { runouts: u64, seed: Option<u64> },
+    /// Enumerates every completion of the board and every holding of the random hands
+    Exact,
+}
+
+/// Computes each player's equity in the given mode
+pub fn calculate_equity(
+    query: &EquityQuery,
+    mode: EquityMode,
+) -> Result<EquityResult, EquityError> {
+    match mode {
+        EquityMode::MonteCarlo { runouts, seed } => {
+            let mut deck = seed.map_or_else(Deck::new, Deck::seeded);
+            monte_carlo_equity(query, runouts, &mut deck)
+        }
+        EquityMode::Exact => exact_equity(query),
+    }
+}
+
+/// Estimates each player's equity by dealing `runouts` random completions of the board, along
+/// with hole cards for the random hands.
+///
+/// Hands are compared with `evaluate_7`, which orders hands the same way as `get_hand_value`.
+pub fn monte_carlo_equity(
+    query: &EquityQuery,
+    runouts: u64,
+    deck: &mut Deck,
+) -> Result<EquityResult, EquityError> {
+    let remaining = query.remaining_cards()?;
+    if runouts == 0 {
+        return Err(EquityError::NoRunouts);
+    }
+    deck.reset();
+    deck.remove_set(&(CardSet::full() - remaining));
+
+    let mut hands = query.hands.clone();
+    let mut board = [Card::from_index(0); BOARD_SIZE];
+    board[..query.board.len()].copy_from_slice(&query.board);
+    let mut tally = Tally::new(query.players());
+    for _ in 0..runouts {
+        deck.shuffle();
+        hands.truncate(query.hands.len());
+        for _ in 0..query.random_hands {
+            hands.push((deck.get_next().unwrap(), deck.get_next().unwrap()));
+        }
+        for card in board.iter_mut().skip(query.board.len()) {
+            *card = deck.get_next().unwrap();
+        }
+        tally.add(&hands, &board, 1.0);
+    }
+    Ok(tally.result(true))
+}
+
+/// Computes each player's exact equity over every board completion and random holding.
+///
+/// Each runout is equally likely, so the result matches published equity tables. Enumerating a
+/// preflop spot visits over a million boards per holding of the random hands.
+pub fn exact_equity(query: &EquityQuery) -> Result<EquityResult, EquityError> {
+    let remaining = query.remaining_cards()?;
+    let mut tally = Tally::new(query.players());
+    let mut hands = query.hands.clone();
+    deal_random_hands(query, remaining, &mut hands, &mut tally);
+    Ok(tally.result(false))
+}
+
+/// Deals every holding of the random hands still to be dealt, then every board
+fn deal_random_hands(
+    query: &EquityQuery,
+    remaining: CardSet,
+    hands: &mut Vec<(Card, Card)>,
+    tally: &mut Tally,
+) {
+    if hands.len() == query.players() {
+        let mut board = [Card::from_index(0); BOARD_SIZE];
+        board[..query.board.len()].copy_from_slice(&query.board);
+        deal_boards(
+            &remaining.to_vec(),
+            &mut board,
+            query.board.len(),
+            0,
+            &mut |board| {
+                tally.add(hands, board, 1.0);
+            },
+        );
+        return;
+    }
+    let cards = remaining.to_vec();
+    for (idx, first) in cards.iter().enumerate() {
+        for second in &cards[idx + 1..] {
+            let mut rest = remaining;
+            rest.remove(first);
+            rest.remove(second);
+            hands.push((*first, *second));
+            deal_random_hands(query, rest, hands, tally);
+            hands.pop();
+        }
+    }
+}
+
+/// Fills the board from `next` onwards with every combination of the cards from `start`,
+/// visiting each complete board
+fn deal_boards(
+    cards: &[Card],
+    board: &mut [Card; BOARD_SIZE],
+    next: usize,
+    start: usize,
+    visit: &mut dyn FnMut(&[Card; BOARD_SIZE]),
+) {
+    if next == BOARD_SIZE {
+        visit(board);
+        return;
+    }
+    for idx in start..cards.len() {
+        board[next] = cards[idx];
+        deal_boards(cards, board, next + 1, idx + 1, visit);
+    }
+}
+
+/// One combo's equity against the other ranges
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComboEquity {
+    pub cards: (Card, Card),
+    /// The combo's weight in its range
+    pub weight: f64,
+    /// The combo's share of the pot, averaged over the runouts it was dealt in
+    pub equity: f64,
+    /// The number of runouts the combo was dealt in, 0 when every one is blocked
+    pub runouts: u64,
+}
+
+/// A range's equity overall and for each of its combos
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeEquity {
+    pub equity: f64,
+    /// The standard error of `equity`, zero when it was computed exactly
+    pub std_error: f64,
+    /// The combos left after removing those blocked by the board
+    pub combos: Vec<ComboEquity>,
+}
+
+/// Each combo's accumulated share of the pot
+#[derive(Clone, Copy, Default)]
+struct ComboTally {
+    share: f64,
+    weight: f64,
+    runouts: u64,
+}
+
+/// Computes the equity of each range on a board, overall and for each combo.
+///
+/// A deal gives every range one combo, with no card used twice. Each deal counts in proportion
+/// to the product of its combos' weights, so combos blocked by another range count less.
+pub fn range_equity(
+    ranges: &[Range],
+    board: &[Card],
+    mode: EquityMode,
+) -> Result<Vec<RangeEquity>, EquityError> {
+    if ranges.len() < 2 {
+        return Err(EquityError::TooFewHands);
+    }
+    let board_cards = board_cards(board)?;
+    let combos: Vec<Vec<Combo>> = ranges
+        .iter()
+        .map(|range| range.without(&board_cards).combos())
+        .collect();
+    let mut tally = Tally::new(ranges.len());
+    let mut combo_tallies: Vec<Vec<ComboTally>> = combos
+        .iter()
+        .map(|combos| vec![ComboTally::default(); combos.len()])
+        .collect();
+
+    let mut record = |tally: &mut Tally, chosen: &[usize], board: &[Card; BOARD_SIZE], weight| {
+        let hands: Vec<(Card, Card)> = chosen
+            .iter()
+            .enumerate()
+            .map(|(range, combo)| combos[range][*combo].cards)
+            .collect();
+        let shares = tally.add(&hands, board, weight);
+        for (range, combo) in chosen.iter().enumerate() {
+            let combo_tally = &mut combo_tallies[range][*combo];
+            combo_tally.share += weight * shares[range];
+            combo_tally.weight += weight;
+            combo_tally.runouts += 1;
+        }
+    };
+
+    let sampled = match mode {
+        EquityMode::Exact => {
+            let mut cards = [Card::from_index(0); BOARD_SIZE];
+            cards[..board.len()].copy_from_slice(board);
+            deal_combos(
+                &combos,
+                board_cards,
+                &mut vec![],
+                1.0,
+                &mut |chosen, weight| {
+                    let used = dealt_cards(&combos, chosen);
+                    let remaining = (CardSet::full() - board_cards - used).to_vec();
+                    deal_boards(&remaining, &mut cards, board.len(), 0, &mut |cards| {
+                        record(&mut tally, chosen, cards, weight)
+                    });
+                },
+            );
+            false
+        }
+        EquityMode::MonteCarlo { runouts, seed } => {
+            if runouts == 0 {
+                return Err(EquityError::NoRunouts);
+            }
+            let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+            let pickers = combos
+                .iter()
+                .map(|combos| WeightedIndex::new(combos.iter().map(|combo| combo.weight)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| EquityError::NoMatchups)?;
+            let mut deck = (CardSet::full() - board_cards).to_vec();
+            let mut cards = [Card::from_index(0); BOARD_SIZE];
+            cards[..board.len()].copy_from_slice(board);
+            for _ in 0..runouts {
+                let chosen = sample_combos(&combos, &pickers, &mut rng)?;
+                let used = dealt_cards(&combos, &chosen);
+                deck.shuffle(&mut rng);
+                let mut undealt = deck.iter().filter(|card| !used.contains(card));
+                for card in cards.iter_mut().skip(board.len()) {
+                    *card = *undealt.next().unwrap();
+                }
+                record(&mut tally, &chosen, &cards, 1.0);
+            }
+            true
+        }
+    };
+    if tally.runouts == 0 {
+        return Err(EquityError::NoMatchups);
+    }
+
+    let result = tally.result(sampled);
+    Ok(result
+        .players
+        .iter()
+        .zip(combos.iter().zip(&combo_tallies))
+        .map(|(player, (combos, combo_tallies))| RangeEquity {
+            equity: player.equity,
+            std_error: player.std_error,
+            combos: combos
+                .iter()
+                .zip(combo_tallies)
+                .map(|(combo, combo_tally)| ComboEquity {
+                    cards: combo.cards,
+                    weight: combo.weight,
+                    equity: if combo_tally.weight > 0.0 {
+                        combo_tally.share / combo_tally.weight
+                    } else {
+                        0.0
+                    },
+                    runouts: combo_tally.runouts,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Checks a board on its own, returning its cards, since the hands are only known once combos
+/// are chosen
+fn board_cards(board: &[Card]) -> Result<CardSet, EquityError> {
+    if board.len() > BOARD_SIZE {
+        return Err(EquityError::TooManyBoardCards(board.len()));
+    }
+    let mut cards = CardSet::new();
+    for card in board {
+        if !cards.insert(*card) {
+            return Err(EquityError::DuplicateCard(*card));
+        }
+    }
+    Ok(cards)
+}
+
+/// The hole cards of the combo chosen from each range
+fn dealt_cards(combos: &[Vec<Combo>], chosen: &[usize]) -> CardSet {
+    chosen
+        .iter()
+        .enumerate()
+        .flat_map(|(range, combo)| {
+            let (first, second) = combos[range][*combo].cards;
+            [first, second]
+        })
+        .collect()
+}
+
+/// Visits every deal of one combo from each range that uses no card twice, with its weight
+fn deal_combos(
+    combos: &[Vec<Combo>],
+    used: CardSet,
+    chosen: &mut Vec<usize>,
+    weight: f64,
+    visit: &mut dyn FnMut(&[usize], f64),
+) {
+    let Some(range) = combos.get(chosen.len()) else {
+        visit(chosen, weight);
+        return;
+    };
+    for (idx, combo) in range.iter().enumerate() {
+        let cards = CardSet::from_iter([combo.cards.0, combo.cards.1]);
+        if !used.is_disjoint(&cards) {
+            continue;
+        }
+        chosen.push(idx);
+        deal_combos(combos, used | cards, chosen, weight * combo.weight, visit);
+        chosen.pop();
+    }
+}
+
+/// How many times a random deal may collide before the ranges are taken to block each other
+const MAX_SAMPLE_ATTEMPTS: usize = 10_000;
+
+/// Picks one combo from each range by weight, redrawing the whole deal when cards collide
+fn sample_combos(
+    combos: &[Vec<Combo>],
+    pickers: &[WeightedIndex<f64>],
+    rng: &mut StdRng,
+) -> Result<Vec<usize>, EquityError> {
+    'attempt: for _ in 0..MAX_SAMPLE_ATTEMPTS {
+        let mut used = CardSet::new();
+        let mut chosen = Vec::with_capacity(combos.len());
+        for (range, picker) in combos.iter().zip(pickers) {
+            let idx = picker.sample(rng);
+            let (first, second) = range[idx].cards;
+            if !used.insert(first) || !used.insert(second) {
+                continue 'attempt;
+            }
+            chosen.push(idx);
+        }
+        return Ok(chosen);
+    }
+    Err(EquityError::NoMatchups)
+}
@@ -0,0 +1,191 @@
+use crate::card::{Card, Rank, Suit};
+use crate::hand_utils::{evaluate_hand, HandCategory, HandRank};
+use std::sync::OnceLock;
+
+const NUM_RANKS: usize = 13;
+const HAND_SIZE: usize = 7;
+const MAX_RANK_COUNT: usize = 4;
+
+/// Precomputed tables for the seven card evaluator.
+///
+/// Flushes are looked up by the rank mask of the flush suit. Every other hand only depends on
+/// how many cards of each rank it holds, so those are looked up through a perfect hash of the
+/// rank counts.
+struct Tables {
+    flush: Vec<u32>,
+    no_flush: Vec<u32>,
+    /// `hash_offsets[rank][remaining][count]` is added to the hash when `count` cards of `rank`
+    /// are held and `remaining` cards have not yet been counted
+    hash_offsets: [[[u32; MAX_RANK_COUNT + 1]; HAND_SIZE + 1]; NUM_RANKS],
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+fn tables() -> &'static Tables {
+    TABLES.get_or_init(build_tables)
+}
+
+/// Packs a hand rank into an integer with the same ordering.
+///
+/// The category takes the top bits and each kicker rank the following four bits, so values
+/// compare exactly as the `HandRank`s they came from.
+pub fn encode_hand_rank(hand: &HandRank) -> u32 {
+    let mut value = (hand.category as u32) << 20;
+    for (idx, rank) in hand.kickers.iter().enumerate() {
+        value |= (*rank as u32) << (16 - 4 * idx);
+    }
+    value
+}
+
+/// The category of a hand value returned by `evaluate_7` or `encode_hand_rank`
+pub fn hand_category(value: u32) -> HandCategory {
+    match value >> 20 {
+        0 => HandCategory::HighCard,
+        1 => HandCategory::Pair,
+        2 => HandCategory::TwoPair,
+        3 => HandCategory::ThreeOfAKind,
+        4 => HandCategory::Straight,
+        5 => HandCategory::Flush,
+        6 => HandCategory::FullHouse,
+        7 => HandCategory::FourOfAKind,
+        _ => HandCategory::StraightFlush,
+    }
+}
+
+/// Evaluates seven distinct cards using precomputed tables.
+///
+/// A higher value is a better hand, with the same ordering as `get_hand_value`. The tables are
+/// built on first use; after that evaluation does not allocate.
+///
+/// Every card must have a real rank and suit; a `Rank::Null` card would index out of the tables.
+pub fn evaluate_7(cards: &[Card; HAND_SIZE]) -> u32 {
+    let tables = tables();
+    let mut rank_counts = [0_usize; NUM_RANKS];
+    let mut suit_masks = [0_u16; 4];
+    for card in cards {
+        debug_assert!(
+            card.rank != Rank::Null,
+            "evaluate_7 was given a card without a rank"
+        );
+        rank_counts[card.rank as usize] += 1;
+        suit_masks[card.suit as usize] |= 1 << (card.rank as usize);
+    }
+
+    // With seven cards a flush can't also hold quads or a full house
+    for mask in suit_masks {
+        if mask.count_ones() >= 5 {
+            return tables.flush[mask as usize];
+        }
+    }
+
+    let mut remaining = HAND_SIZE;
+    let mut hash = 0;
+    for (rank, count) in rank_counts.iter().enumerate() {
+        hash += tables.hash_offsets[rank][remaining][*count];
+        remaining -= count;
+    }
+    tables.no_flush[hash as usize]
+}
+
+/// Builds the perfect hash offsets, also returning the number of distinct hashes
+fn build_hash_offsets() -> (
+    [[[u32; MAX_RANK_COUNT + 1]; HAND_SIZE + 1]; NUM_RANKS],
+    usize,
+) {
+    // rank_count_combinations[n][k] is the number of ways n ranks can hold k cards in total
+    let mut rank_count_combinations = [[0_u32; HAND_SIZE + 1]; NUM_RANKS + 1];
+    rank_count_combinations[0][0] = 1;
+    for n in 1..=NUM_RANKS {
+        for k in 0..=HAND_SIZE {
+            rank_count_combinations[n][k] = (0..=MAX_RANK_COUNT.min(k))
+                .map(|count| rank_count_combinations[n - 1][k - count])
+                .sum();
+        }
+    }
+
+    let mut hash_offsets = [[[0_u32; MAX_RANK_COUNT + 1]; HAND_SIZE + 1]; NUM_RANKS];
+    for (rank, offsets) in hash_offsets.iter_mut().enumerate() {
+        let ranks_after = NUM_RANKS - rank - 1;
+        for (remaining, offset) in offsets.iter_mut().enumerate() {
+            for count in 1..=MAX_RANK_COUNT.min(remaining) {
+                offset[count] =
+                    offset[count - 1] + rank_count_combinations[ranks_after][remaining - count + 1];
+            }
+        }
+    }
+    (
+        hash_offsets,
+        rank_count_combinations[NUM_RANKS][HAND_SIZE] as usize,
+    )
+}
+
+fn fill_no_flush(
+    tables: &mut Tables,
+    rank_counts: &mut [usize; NUM_RANKS],
+    rank: usize,
+    remaining: usize,
+) {
+    if rank == NUM_RANKS {
+        if remaining > 0 {
+            return;
+        }
+        // Spread the cards over the suits so that no flush is possible
+        let mut suit_fill = [0; 4];
+        let suits: Vec<Suit> = Suit::iterator().copied().collect();
+        let mut cards = vec![];
+        let mut remaining = HAND_SIZE;
+        let mut hash = 0;
+        for (rank, count) in rank_counts.iter().enumerate() {
+            let mut used = [false; 4];
+            for _ in 0..*count {
+                let suit = (0..4)
+                    .filter(|suit| !used[*suit])
+                    .min_by_key(|suit| suit_fill[*suit])
+                    .unwrap();
+                used[suit] = true;
+                suit_fill[suit] += 1;
+                cards.push(Card {
+                    rank: Rank::from(rank as i64),
+                    suit: suits[suit],
+                });
+            }
+            hash += tables.hash_offsets[rank][remaining][*count];
+            remaining -= count;
+        }
+        tables.no_flush[hash as usize] = encode_hand_rank(&evaluate_hand(&cards));
+        return;
+    }
+
+    for count in 0..=MAX_RANK_COUNT.min(remaining) {
+        rank_counts[rank] = count;
+        fill_no_flush(tables, rank_counts, rank + 1, remaining - count);
+    }
+    rank_counts[rank] = 0;
+}
+
+fn build_tables() -> Tables {
+    let (hash_offsets, num_hashes) = build_hash_offsets();
+    let mut tables = Tables {
+        flush: vec![0; 1 << NUM_RANKS],
+        no_flush: vec![0; num_hashes],
+        hash_offsets,
+    };
+
+    for mask in 0..(1_usize << NUM_RANKS) {
+        let count = mask.count_ones() as usize;
+        if !(5..=HAND_SIZE).contains(&count) {
+            continue;
+        }
+        let cards: Vec<Card> = (0..NUM_RANKS)
+            .filter(|rank| mask & (1 << rank) != 0)
+            .map(|rank| Card {
+                rank: Rank::from(rank as i64),
+                suit: Suit::Heart,
+            })
+            .collect();
+        tables.flush[mask] = encode_hand_rank(&evaluate_hand(&cards));
+    }
+
+    fill_no_flush(&mut tables, &mut [0; NUM_RANKS], 0, HAND_SIZE);
+    tables
+}
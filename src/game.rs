@@ -1,9 +1,14 @@
-/// A generic player of a game
-pub trait GamePlayer<StateT, ActionT> {
-    fn get_action(&self, state: &StateT, actions: &Vec<ActionT>) -> ActionT;
+/// A generic player of a game.
+///
+/// By default players choose from a list of actions, but games whose actions take parameters
+/// can describe the legal actions with their own type.
+pub trait GamePlayer<StateT, ActionT, LegalActionsT: ?Sized = [ActionT]> {
+    fn get_action(&self, state: &StateT, actions: &LegalActionsT) -> ActionT;
 }
 
 /// A generic game engine
 pub trait GameEngine<StateT, ActionT> {
-    fn run(&mut self);
+    type Error;
+
+    fn run(&mut self) -> Result<(), Self::Error>;
 }
@@ -0,0 +1,1164 @@
+use crate::card::{format_cards, parse_cards, Card, ParseCardError, Rank};
+use crate::hand_utils::{evaluate_hand, HandCategory, HandRank};
+use crate::poker::{
+    assign_positions, blind_bets, build_pots, BettingSituation, BettingStructure, Blind, HandEvent,
+    HistoryEntry, LegalActions, PokerAction, PokerState, Street,
+};
+
+use std::collections::hash_map::HashMap;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Everything needed to write out or replay one finished hand
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandRecord {
+    pub hand_number: u64,
+    /// When the hand started, in seconds since the Unix epoch
+    pub started_at: u64,
+    pub table_name: String,
+    pub num_seats: usize,
+    /// The seat with the dealer button, counted from 0
+    pub button: usize,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub betting_structure: BettingStructure,
+    /// Player names by seat. Players without a name are written as `Player n`.
+    pub names: HashMap<usize, String>,
+    /// The stacks of the players dealt in, before any chips were posted
+    pub starting_stacks: HashMap<usize, u64>,
+    pub history: Vec<HistoryEntry>,
+}
+
+impl HandRecord {
+    pub fn name(&self, player: usize) -> String {
+        self.names
+            .get(&player)
+            .cloned()
+            .unwrap_or_else(|| format!("Player {}", player))
+    }
+
+    /// The seats dealt into the hand from the one after the button round to the button
+    fn seats_from_button(&self) -> Vec<usize> {
+        let mut seats: Vec<usize> = self.starting_stacks.keys().copied().collect();
+        seats.sort_by_key(|seat| (seat + self.num_seats - self.button - 1) % self.num_seats);
+        seats
+    }
+}
+
+/// What happened to each player, gathered from the history for the showdown and summary
+struct HandOutcome {
+    /// How many players were dealt into the hand
+    players: usize,
+    /// The hole cards the history knows, which may be only some players'
+    hole_cards: HashMap<usize, (Card, Card)>,
+    board: Vec<Card>,
+    /// The street each player folded on
+    folded: HashMap<usize, Street>,
+    small_blind: Option<usize>,
+    big_blind: Option<usize>,
+    /// Players who put chips in other than antes
+    bet: HashSet<usize>,
+    /// Chips won by each player from each pot
+    winnings: Vec<(usize, usize, u64)>,
+    pot_sizes: Vec<u64>,
+}
+
+impl HandOutcome {
+    fn new(history: &[HistoryEntry], players: usize) -> Self {
+        let mut outcome = HandOutcome {
+            players,
+            hole_cards: HashMap::new(),
+            board: vec![],
+            folded: HashMap::new(),
+            small_blind: None,
+            big_blind: None,
+            bet: HashSet::new(),
+            winnings: vec![],
+            pot_sizes: vec![],
+        };
+        for entry in history {
+            match &entry.event {
+                HandEvent::HoleCards { player, cards } => {
+                    outcome.hole_cards.insert(*player, *cards);
+                }
+                HandEvent::CommunityCards { cards } => outcome.board.extend(cards),
+                HandEvent::PostBlind { player, blind, .. } => {
+                    match blind {
+                        Blind::Small => outcome.small_blind = Some(*player),
+                        Blind::Big => outcome.big_blind = Some(*player),
+                        Blind::Straddle => (),
+                    }
+                    outcome.bet.insert(*player);
+                }
+                HandEvent::Action {
+                    player,
+                    action: PokerAction::Fold,
+                    ..
+                } => {
+                    outcome.folded.insert(*player, entry.street);
+                }
+                HandEvent::Action { player, amount, .. } if *amount > 0 => {
+                    outcome.bet.insert(*player);
+                }
+                HandEvent::WinsPot {
+                    player,
+                    pot,
+                    amount,
+                } => {
+                    if outcome.pot_sizes.len() <= *pot {
+                        outcome.pot_sizes.resize(pot + 1, 0);
+                    }
+                    outcome.pot_sizes[*pot] += amount;
+                    outcome.winnings.push((*player, *pot, *amount));
+                }
+                _ => (),
+            }
+        }
+        outcome
+    }
+
+    /// The hand only goes to showdown if more than one player is left at the end
+    fn is_showdown(&self) -> bool {
+        self.players.saturating_sub(self.folded.len()) > 1
+    }
+
+    fn won_by(&self, player: usize) -> u64 {
+        self.winnings
+            .iter()
+            .filter(|(winner, _, _)| *winner == player)
+            .map(|(_, _, amount)| amount)
+            .sum()
+    }
+
+    fn best_hand(&self, (card1, card2): (Card, Card)) -> HandRank {
+        let mut cards = vec![card1, card2];
+        cards.extend(&self.board);
+        evaluate_hand(&cards)
+    }
+}
+
+fn rank_name(rank: Rank) -> String {
+    format!("{:?}", rank)
+}
+
+fn rank_plural(rank: Rank) -> String {
+    match rank {
+        Rank::Six => "Sixes".to_string(),
+        _ => format!("{:?}s", rank),
+    }
+}
+
+/// Describes a hand the way PokerStars does, such as `a pair of Kings` or `a flush, Ace high`
+pub fn describe_hand(hand: &HandRank) -> String {
+    let top = hand.kickers[0];
+    // The lowest card of a straight comes last, which is the ace in a wheel
+    let low = hand.cards.last().map_or(Rank::Null, |card| card.rank);
+    match hand.category {
+        HandCategory::HighCard => format!("high card {}", rank_name(top)),
+        HandCategory::Pair => format!("a pair of {}", rank_plural(top)),
+        HandCategory::TwoPair => format!(
+            "two pair, {} and {}",
+            rank_plural(top),
+            rank_plural(hand.kickers[1])
+        ),
+        HandCategory::ThreeOfAKind => format!("three of a kind, {}", rank_plural(top)),
+        HandCategory::Straight => {
+            format!("a straight, {} to {}", rank_name(low), rank_name(top))
+        }
+        HandCategory::Flush => format!("a flush, {} high", rank_name(top)),
+        HandCategory::FullHouse => format!(
+            "a full house, {} full of {}",
+            rank_plural(top),
+            rank_plural(hand.kickers[1])
+        ),
+        HandCategory::FourOfAKind => format!("four of a kind, {}", rank_plural(top)),
+        HandCategory::StraightFlush if top == Rank::Ace => "a Royal Flush".to_string(),
+        HandCategory::StraightFlush => {
+            format!("a straight flush, {} to {}", rank_name(low), rank_name(top))
+        }
+    }
+}
+
+/// Converts days since the Unix epoch to a (year, month, day) date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month as u32, day as u32)
+}
+
+/// Formats a Unix time as `YYYY/MM/DD HH:MM:SS UTC`
+fn format_timestamp(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    format!(
+        "{}/{:02}/{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+fn street_name(street: Street) -> &'static str {
+    match street {
+        Street::Preflop => "Preflop",
+        Street::Flop => "Flop",
+        Street::Turn => "Turn",
+        Street::River => "River",
+    }
+}
+
+fn pot_name(pot: usize, num_pots: usize) -> String {
+    match pot {
+        _ if num_pots == 1 => "pot".to_string(),
+        0 => "main pot".to_string(),
+        _ => format!("side pot-{}", pot),
+    }
+}
+
+impl HandRecord {
+    fn write_header(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (game, stakes) = match self.betting_structure {
+            BettingStructure::NoLimit => ("No Limit", (self.small_blind, self.big_blind)),
+            BettingStructure::PotLimit => ("Pot Limit", (self.small_blind, self.big_blind)),
+            BettingStructure::FixedLimit {
+                small_bet, big_bet, ..
+            } => ("Limit", (small_bet, big_bet)),
+        };
+        writeln!(
+            f,
+            "PokerStars Hand #{}:  Hold'em {} ({}/{}) - {}",
+            self.hand_number,
+            game,
+            stakes.0,
+            stakes.1,
+            format_timestamp(self.started_at)
+        )?;
+        writeln!(
+            f,
+            "Table '{}' {}-max Seat #{} is the button",
+            self.table_name,
+            self.num_seats,
+            self.button + 1
+        )?;
+        let mut seats: Vec<(&usize, &u64)> = self.starting_stacks.iter().collect();
+        seats.sort();
+        for (seat, stack) in seats {
+            writeln!(
+                f,
+                "Seat {}: {} ({} in chips)",
+                seat + 1,
+                self.name(*seat),
+                stack
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the posts, cards and actions of every street
+    fn write_streets(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut street = Street::Preflop;
+        let mut street_bets = HashMap::<usize, u64>::new();
+        let mut current_bet = 0;
+        let mut board = vec![];
+        let mut dealt = false;
+        for entry in &self.history {
+            if entry.street != street {
+                street = entry.street;
+                street_bets.clear();
+                current_bet = 0;
+            }
+            match &entry.event {
+                HandEvent::PostAnte { player, amount } => {
+                    writeln!(f, "{}: posts the ante {}", self.name(*player), amount)?;
+                }
+                HandEvent::PostBlind {
+                    player,
+                    blind,
+                    amount,
+                } => {
+                    let blind = match blind {
+                        Blind::Small => "small blind",
+                        Blind::Big => "big blind",
+                        Blind::Straddle => "straddle",
+                    };
+                    writeln!(f, "{}: posts {} {}", self.name(*player), blind, amount)?;
+                    let total = street_bets.entry(*player).or_insert(0);
+                    *total += amount;
+                    current_bet = current_bet.max(*total);
+                }
+                HandEvent::HoleCards { player, cards } => {
+                    if !dealt {
+                        writeln!(f, "*** HOLE CARDS ***")?;
+                        dealt = true;
+                    }
+                    writeln!(
+                        f,
+                        "Dealt to {} [{}]",
+                        self.name(*player),
+                        format_cards(&[cards.0, cards.1])
+                    )?;
+                }
+                HandEvent::Action {
+                    player,
+                    action,
+                    amount,
+                    all_in,
+                } => {
+                    let name = self.name(*player);
+                    let all_in = if *all_in { " and is all-in" } else { "" };
+                    let total = street_bets.entry(*player).or_insert(0);
+                    *total += amount;
+                    match action {
+                        PokerAction::Fold => writeln!(f, "{}: folds", name)?,
+                        PokerAction::Check => writeln!(f, "{}: checks", name)?,
+                        PokerAction::Call => writeln!(f, "{}: calls {}{}", name, amount, all_in)?,
+                        PokerAction::Raise(_) if current_bet == 0 => {
+                            writeln!(f, "{}: bets {}{}", name, amount, all_in)?
+                        }
+                        PokerAction::Raise(_) => writeln!(
+                            f,
+                            "{}: raises {} to {}{}",
+                            name,
+                            *total - current_bet,
+                            total,
+                            all_in
+                        )?,
+                    }
+                    current_bet = current_bet.max(*total);
+                }
+                HandEvent::CommunityCards { cards } => {
+                    let label = street_name(entry.street).to_uppercase();
+                    if board.is_empty() {
+                        writeln!(f, "*** {} *** [{}]", label, format_cards(cards))?;
+                    } else {
+                        writeln!(
+                            f,
+                            "*** {} *** [{}] [{}]",
+                            label,
+                            format_cards(&board),
+                            format_cards(cards)
+                        )?;
+                    }
+                    board.extend(cards);
+                }
+                HandEvent::UncalledBetReturned { player, amount } => {
+                    writeln!(
+                        f,
+                        "Uncalled bet ({}) returned to {}",
+                        amount,
+                        self.name(*player)
+                    )?;
+                }
+                // Pots are written out with the showdown
+                HandEvent::WinsPot { .. } => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_showdown(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        outcome: &HandOutcome,
+    ) -> std::fmt::Result {
+        if outcome.is_showdown() {
+            writeln!(f, "*** SHOW DOWN ***")?;
+            for seat in self.seats_from_button() {
+                if outcome.folded.contains_key(&seat) {
+                    continue;
+                }
+                // Only some players' cards are known in a history read from another site
+                match outcome.hole_cards.get(&seat) {
+                    Some(&(card1, card2)) => writeln!(
+                        f,
+                        "{}: shows [{}] ({})",
+                        self.name(seat),
+                        format_cards(&[card1, card2]),
+                        describe_hand(&outcome.best_hand((card1, card2)))
+                    )?,
+                    None => writeln!(f, "{}: mucks hand", self.name(seat))?,
+                }
+            }
+        }
+        for (player, pot, amount) in &outcome.winnings {
+            writeln!(
+                f,
+                "{} collected {} from {}",
+                self.name(*player),
+                amount,
+                pot_name(*pot, outcome.pot_sizes.len())
+            )?;
+        }
+        if !outcome.is_showdown() {
+            for (player, _, _) in &outcome.winnings {
+                writeln!(f, "{}: doesn't show hand", self.name(*player))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_summary(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        outcome: &HandOutcome,
+    ) -> std::fmt::Result {
+        writeln!(f, "*** SUMMARY ***")?;
+        write!(f, "Total pot {}", outcome.pot_sizes.iter().sum::<u64>())?;
+        if outcome.pot_sizes.len() > 1 {
+            write!(f, " Main pot {}.", outcome.pot_sizes[0])?;
+            for (pot, amount) in outcome.pot_sizes.iter().enumerate().skip(1) {
+                write!(f, " Side pot-{} {}.", pot, amount)?;
+            }
+        }
+        writeln!(f, " | Rake 0")?;
+        if !outcome.board.is_empty() {
+            writeln!(f, "Board [{}]", format_cards(&outcome.board))?;
+        }
+
+        let mut seats: Vec<usize> = self.starting_stacks.keys().copied().collect();
+        seats.sort();
+        for seat in seats {
+            write!(f, "Seat {}: {}", seat + 1, self.name(seat))?;
+            if seat == self.button {
+                write!(f, " (button)")?;
+            }
+            if outcome.small_blind == Some(seat) {
+                write!(f, " (small blind)")?;
+            }
+            if outcome.big_blind == Some(seat) {
+                write!(f, " (big blind)")?;
+            }
+
+            let won = outcome.won_by(seat);
+            match outcome.folded.get(&seat) {
+                Some(Street::Preflop) => {
+                    write!(f, " folded before Flop")?;
+                    if !outcome.bet.contains(&seat) {
+                        write!(f, " (didn't bet)")?;
+                    }
+                }
+                Some(street) => write!(f, " folded on the {}", street_name(*street))?,
+                None if outcome.is_showdown() => match outcome.hole_cards.get(&seat) {
+                    Some(&(card1, card2)) => {
+                        let hand = describe_hand(&outcome.best_hand((card1, card2)));
+                        write!(f, " showed [{}] and ", format_cards(&[card1, card2]))?;
+                        if won > 0 {
+                            write!(f, "won ({}) with {}", won, hand)?;
+                        } else {
+                            write!(f, "lost with {}", hand)?;
+                        }
+                    }
+                    None if won > 0 => write!(f, " collected ({})", won)?,
+                    None => write!(f, " mucked")?,
+                },
+                None => write!(f, " collected ({})", won)?,
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the hand in the PokerStars hand history format
+impl Display for HandRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = HandOutcome::new(&self.history, self.starting_stacks.len());
+        self.write_header(f)?;
+        self.write_streets(f)?;
+        self.write_showdown(f, &outcome)?;
+        self.write_summary(f, &outcome)
+    }
+}
+
+/// Writes hands in the PokerStars format, separated by blank lines as in a history file
+pub fn write_hands<W: Write>(writer: &mut W, hands: &[HandRecord]) -> std::io::Result<()> {
+    for hand in hands {
+        write!(writer, "{}\n\n\n", hand)?;
+    }
+    Ok(())
+}
+
+/// Why a hand history couldn't be parsed
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseHandError {
+    /// The text doesn't start with a PokerStars hand header
+    MissingHeader,
+    /// A line that looked like part of the hand but couldn't be read
+    InvalidLine(String),
+    InvalidAmount(String),
+    InvalidCards(ParseCardError),
+    /// An event that doesn't fit the rest of the hand when replayed, such as a bet larger than
+    /// the player's stack or a player without a seat
+    InvalidHistory(String),
+}
+
+impl Display for ParseHandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseHandError::MissingHeader => write!(f, "missing PokerStars hand header"),
+            ParseHandError::InvalidLine(line) => write!(f, "invalid line \"{}\"", line),
+            ParseHandError::InvalidAmount(amount) => write!(f, "invalid amount \"{}\"", amount),
+            ParseHandError::InvalidCards(error) => write!(f, "{}", error),
+            ParseHandError::InvalidHistory(reason) => write!(f, "invalid history: {}", reason),
+        }
+    }
+}
+
+impl Error for ParseHandError {}
+
+impl From<ParseCardError> for ParseHandError {
+    fn from(error: ParseCardError) -> Self {
+        ParseHandError::InvalidCards(error)
+    }
+}
+
+/// Converts a date to days since the Unix epoch, the inverse of `civil_from_days`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Reads the first `YYYY/MM/DD HH:MM:SS` date in the header, ignoring its time zone
+fn parse_timestamp(header: &str) -> Option<u64> {
+    let tokens: Vec<&str> = header.split_whitespace().collect();
+    tokens.windows(2).find_map(|pair| {
+        let date: Vec<&str> = pair[0].trim_start_matches('[').split('/').collect();
+        let time: Vec<&str> = pair[1].split(':').collect();
+        if date.len() != 3 || date[0].len() != 4 || time.len() != 3 {
+            return None;
+        }
+        let days = days_from_civil(
+            date[0].parse().ok()?,
+            date[1].parse().ok()?,
+            date[2].parse().ok()?,
+        );
+        let seconds = time[0].parse::<u64>().ok()? * 3600
+            + time[1].parse::<u64>().ok()? * 60
+            + time[2].parse::<u64>().ok()?;
+        Some(days as u64 * 86_400 + seconds)
+    })
+}
+
+/// The cards in the `[...]` group starting at `start`
+fn cards_at(line: &str, start: usize) -> Result<Vec<Card>, ParseHandError> {
+    let invalid = || ParseHandError::InvalidLine(line.to_string());
+    let cards = line[start..].strip_prefix('[').ok_or_else(invalid)?;
+    let end = cards.find(']').ok_or_else(invalid)?;
+    Ok(parse_cards(&cards[..end])?)
+}
+
+/// The cards in the last `[...]` group of a line
+fn last_cards(line: &str) -> Result<Vec<Card>, ParseHandError> {
+    let start = line
+        .rfind('[')
+        .ok_or_else(|| ParseHandError::InvalidLine(line.to_string()))?;
+    cards_at(line, start)
+}
+
+fn hole_pair(line: &str, cards: &[Card]) -> Result<(Card, Card), ParseHandError> {
+    match cards {
+        [card1, card2] => Ok((*card1, *card2)),
+        _ => Err(ParseHandError::InvalidLine(line.to_string())),
+    }
+}
+
+/// Reads one hand, keeping track of the bets on each street to turn PokerStars' totals back
+/// into the engine's raise amounts
+struct HandParser {
+    record: HandRecord,
+    /// Whether amounts are currency, read in hundredths
+    cents: bool,
+    street: Street,
+    street_bets: HashMap<usize, u64>,
+    current_bet: u64,
+    hole_cards: HashMap<usize, (Card, Card)>,
+}
+
+impl HandParser {
+    fn parse_amount(&self, amount: &str) -> Result<u64, ParseHandError> {
+        let invalid = || ParseHandError::InvalidAmount(amount.to_string());
+        let digits = amount
+            .trim()
+            .trim_start_matches(['$', '€', '£'])
+            .split_whitespace()
+            .next()
+            .ok_or_else(invalid)?;
+        if !self.cents {
+            return digits.parse().map_err(|_| invalid());
+        }
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, "0"));
+        if fraction.len() > 2 {
+            return Err(invalid());
+        }
+        let whole: u64 = whole.parse().map_err(|_| invalid())?;
+        let fraction: u64 = format!("{:0<2}", fraction).parse().map_err(|_| invalid())?;
+        Ok(whole * 100 + fraction)
+    }
+
+    fn parse_header(header: &str) -> Result<Self, ParseHandError> {
+        if !header.starts_with("PokerStars ") {
+            return Err(ParseHandError::MissingHeader);
+        }
+        let invalid = || ParseHandError::InvalidLine(header.to_string());
+        let hand_number = header
+            .split_once('#')
+            .and_then(|(_, rest)| rest.split(|c: char| !c.is_ascii_digit()).next())
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(invalid)?;
+        let stakes = header
+            .split('(')
+            .skip(1)
+            .filter_map(|group| group.split_once(')').map(|(stakes, _)| stakes))
+            .find(|group| group.contains('/'))
+            .ok_or_else(invalid)?;
+        let mut parser = HandParser {
+            record: HandRecord {
+                hand_number,
+                started_at: parse_timestamp(header).unwrap_or(0),
+                table_name: String::new(),
+                num_seats: 0,
+                button: 0,
+                small_blind: 0,
+                big_blind: 0,
+                betting_structure: BettingStructure::NoLimit,
+                names: HashMap::new(),
+                starting_stacks: HashMap::new(),
+                history: vec![],
+            },
+            cents: stakes.contains(['$', '€', '£', '.']),
+            street: Street::Preflop,
+            street_bets: HashMap::new(),
+            current_bet: 0,
+            hole_cards: HashMap::new(),
+        };
+        let (small, big) = stakes.split_once('/').ok_or_else(invalid)?;
+        let (small, big) = (parser.parse_amount(small)?, parser.parse_amount(big)?);
+        let record = &mut parser.record;
+        if header.contains("No Limit") {
+            record.small_blind = small;
+            record.big_blind = big;
+        } else if header.contains("Pot Limit") {
+            record.small_blind = small;
+            record.big_blind = big;
+            record.betting_structure = BettingStructure::PotLimit;
+        } else {
+            // Limit games are described by their bet sizes, with blinds of half a small bet
+            // and a small bet
+            record.small_blind = small / 2;
+            record.big_blind = small;
+            record.betting_structure = BettingStructure::FixedLimit {
+                small_bet: small,
+                big_bet: big,
+                raise_cap: 4,
+            };
+        }
+        Ok(parser)
+    }
+
+    fn parse_table(&mut self, line: &str) -> Result<(), ParseHandError> {
+        let invalid = || ParseHandError::InvalidLine(line.to_string());
+        let rest = line.strip_prefix("Table '").ok_or_else(invalid)?;
+        let (name, rest) = rest.rsplit_once('\'').ok_or_else(invalid)?;
+        self.record.table_name = name.to_string();
+        for token in rest.split_whitespace() {
+            if let Some(seats) = token.strip_suffix("-max") {
+                self.record.num_seats = seats.parse().map_err(|_| invalid())?;
+            } else if let Some(seat) = token.strip_prefix('#') {
+                let seat: usize = seat.parse().map_err(|_| invalid())?;
+                self.record.button = seat.checked_sub(1).ok_or_else(invalid)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_seat(&mut self, line: &str) -> Result<(), ParseHandError> {
+        let invalid = || ParseHandError::InvalidLine(line.to_string());
+        let rest = line.strip_prefix("Seat ").ok_or_else(invalid)?;
+        let (seat, rest) = rest.split_once(": ").ok_or_else(invalid)?;
+        let seat: usize = seat.parse().map_err(|_| invalid())?;
+        let seat = seat.checked_sub(1).ok_or_else(invalid)?;
+        let chips = rest.rfind(" in chips").ok_or_else(invalid)?;
+        let open = rest[..chips].rfind(" (").ok_or_else(invalid)?;
+        let stack = self.parse_amount(&rest[open + 2..chips])?;
+        self.record.names.insert(seat, rest[..open].to_string());
+        if !rest.contains("is sitting out") {
+            self.record.starting_stacks.insert(seat, stack);
+        }
+        self.record.num_seats = self.record.num_seats.max(seat + 1);
+        Ok(())
+    }
+
+    /// The player whose name starts the line followed by `separator`, and the rest of the line
+    fn player_line<'a>(&self, line: &'a str, separator: &str) -> Option<(usize, &'a str)> {
+        self.record
+            .names
+            .iter()
+            .filter_map(|(seat, name)| {
+                line.strip_prefix(name.as_str())
+                    .and_then(|rest| rest.strip_prefix(separator))
+                    .map(|rest| (*seat, name.len(), rest))
+            })
+            .max_by_key(|(_, len, _)| *len)
+            .map(|(seat, _, rest)| (seat, rest))
+    }
+
+    fn record(&mut self, event: HandEvent) {
+        self.record.history.push(HistoryEntry {
+            street: self.street,
+            event,
+        });
+    }
+
+    fn post(&mut self, player: usize, blind: Blind, amount: u64) {
+        let total = self.street_bets.entry(player).or_insert(0);
+        *total += amount;
+        self.current_bet = self.current_bet.max(*total);
+        self.record(HandEvent::PostBlind {
+            player,
+            blind,
+            amount,
+        });
+    }
+
+    fn parse_player_action(&mut self, player: usize, action: &str) -> Result<(), ParseHandError> {
+        let invalid = || ParseHandError::InvalidLine(action.to_string());
+        let (action, all_in) = match action.strip_suffix(" and is all-in") {
+            Some(action) => (action, true),
+            None => (action, false),
+        };
+        let (verb, amounts) = action.split_once(' ').unwrap_or((action, ""));
+        let committed = *self.street_bets.get(&player).unwrap_or(&0);
+        let (action, amount) = match verb {
+            "folds" => (PokerAction::Fold, 0),
+            "checks" => (PokerAction::Check, 0),
+            "calls" => (PokerAction::Call, self.parse_amount(amounts)?),
+            "bets" => {
+                let amount = self.parse_amount(amounts)?;
+                (PokerAction::Raise(amount), amount)
+            }
+            "raises" => {
+                let (_, total) = amounts.split_once(" to ").ok_or_else(invalid)?;
+                let total = self.parse_amount(total)?;
+                let raise = total.checked_sub(self.current_bet).ok_or_else(invalid)?;
+                let amount = total.checked_sub(committed).ok_or_else(invalid)?;
+                (PokerAction::Raise(raise), amount)
+            }
+            "posts" => {
+                if let Some(amount) = amounts.strip_prefix("small blind ") {
+                    let amount = self.parse_amount(amount)?;
+                    self.post(player, Blind::Small, amount);
+                } else if let Some(amount) = amounts.strip_prefix("big blind ") {
+                    let amount = self.parse_amount(amount)?;
+                    self.post(player, Blind::Big, amount);
+                } else if let Some(amount) = amounts.strip_prefix("straddle ") {
+                    let amount = self.parse_amount(amount)?;
+                    self.post(player, Blind::Straddle, amount);
+                } else if let Some(amount) = amounts.strip_prefix("the ante ") {
+                    let amount = self.parse_amount(amount)?;
+                    self.record(HandEvent::PostAnte { player, amount });
+                } else if let Some(amount) = amounts.strip_prefix("small & big blinds ") {
+                    // The small blind part of a missed blind is dead money
+                    let amount = self.parse_amount(amount)?;
+                    let dead = self.record.small_blind.min(amount);
+                    self.record(HandEvent::PostAnte {
+                        player,
+                        amount: dead,
+                    });
+                    self.post(player, Blind::Big, amount - dead);
+                } else {
+                    return Err(invalid());
+                }
+                return Ok(());
+            }
+            "shows" => {
+                let cards = last_cards(amounts)?;
+                self.hole_cards.insert(player, hole_pair(amounts, &cards)?);
+                return Ok(());
+            }
+            // Mucks, chat, sitting out and the like don't change the hand
+            _ => return Ok(()),
+        };
+        let total = self.street_bets.entry(player).or_insert(0);
+        *total += amount;
+        self.current_bet = self.current_bet.max(*total);
+        self.record(HandEvent::Action {
+            player,
+            action,
+            amount,
+            all_in,
+        });
+        Ok(())
+    }
+
+    fn parse_street(&mut self, line: &str) -> Result<(), ParseHandError> {
+        let street = if line.starts_with("*** FLOP ***") {
+            Street::Flop
+        } else if line.starts_with("*** TURN ***") {
+            Street::Turn
+        } else if line.starts_with("*** RIVER ***") {
+            Street::River
+        } else {
+            return Ok(());
+        };
+        self.street = street;
+        self.street_bets.clear();
+        self.current_bet = 0;
+        let cards = last_cards(line)?;
+        self.record(HandEvent::CommunityCards { cards });
+        Ok(())
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), ParseHandError> {
+        let invalid = || ParseHandError::InvalidLine(line.to_string());
+        if line.starts_with("***") {
+            return self.parse_street(line);
+        }
+        if let Some(rest) = line.strip_prefix("Dealt to ") {
+            let (player, cards) = self.player_line(rest, " ").ok_or_else(invalid)?;
+            let cards = cards_at(cards, 0)?;
+            self.hole_cards.insert(player, hole_pair(line, &cards)?);
+        } else if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (amount, name) = rest.split_once(") returned to ").ok_or_else(invalid)?;
+            let amount = self.parse_amount(amount)?;
+            let player = self
+                .record
+                .names
+                .iter()
+                .find(|(_, known)| known.as_str() == name)
+                .map(|(seat, _)| *seat)
+                .ok_or_else(invalid)?;
+            self.record(HandEvent::UncalledBetReturned { player, amount });
+        } else if let Some((player, rest)) = self.player_line(line, " collected ") {
+            let (amount, pot) = rest.split_once(" from ").ok_or_else(invalid)?;
+            let amount = self.parse_amount(amount)?;
+            let pot = match pot.strip_prefix("side pot") {
+                Some(side) => side.trim_start_matches('-').parse().unwrap_or(1),
+                None => 0,
+            };
+            self.record(HandEvent::WinsPot {
+                player,
+                pot,
+                amount,
+            });
+        } else if let Some((player, action)) = self.player_line(line, ": ") {
+            self.parse_player_action(player, action)?;
+        }
+        Ok(())
+    }
+
+    /// Reads cards shown or mucked at showdown from a summary line
+    fn parse_summary(&mut self, line: &str) -> Result<(), ParseHandError> {
+        let Some(rest) = line.strip_prefix("Seat ") else {
+            return Ok(());
+        };
+        let Some((seat, _)) = rest.split_once(": ") else {
+            return Ok(());
+        };
+        let Ok(seat) = seat.parse::<usize>() else {
+            return Ok(());
+        };
+        for marker in ["showed ", "mucked "] {
+            if let Some(start) = rest.find(marker) {
+                let cards = cards_at(rest, start + marker.len())?;
+                self.hole_cards
+                    .insert(seat.saturating_sub(1), hole_pair(line, &cards)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Puts the known hole cards right after the antes and blinds, where the engine deals them
+    fn finish(mut self) -> HandRecord {
+        let hole_cards: Vec<HistoryEntry> = self
+            .record
+            .seats_from_button()
+            .into_iter()
+            .filter_map(|player| {
+                self.hole_cards.get(&player).map(|cards| HistoryEntry {
+                    street: Street::Preflop,
+                    event: HandEvent::HoleCards {
+                        player,
+                        cards: *cards,
+                    },
+                })
+            })
+            .collect();
+        let dealt_at = self
+            .record
+            .history
+            .iter()
+            .position(|entry| {
+                !matches!(
+                    entry.event,
+                    HandEvent::PostAnte { .. } | HandEvent::PostBlind { .. }
+                )
+            })
+            .unwrap_or(self.record.history.len());
+        self.record.history.splice(dealt_at..dealt_at, hole_cards);
+        self.record
+    }
+}
+
+/// Parses a single hand in the PokerStars format.
+///
+/// Amounts in a currency are read in cents. Only hole cards that were dealt to the player whose
+/// history it is or shown at showdown are known.
+pub fn parse_hand(text: &str) -> Result<HandRecord, ParseHandError> {
+    let mut lines = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty());
+    let mut parser = HandParser::parse_header(lines.next().ok_or(ParseHandError::MissingHeader)?)?;
+    let mut in_summary = false;
+    let mut seats_done = false;
+    for line in lines {
+        if line.starts_with("Table '") {
+            parser.parse_table(line)?;
+        } else if line.starts_with("*** SUMMARY ***") {
+            in_summary = true;
+        } else if in_summary {
+            parser.parse_summary(line)?;
+        } else if !seats_done && line.starts_with("Seat ") {
+            parser.parse_seat(line)?;
+        } else {
+            seats_done = true;
+            parser.parse_line(line)?;
+        }
+    }
+    Ok(parser.finish())
+}
+
+/// Parses every hand in a PokerStars hand history file
+pub fn parse_hands(text: &str) -> Result<Vec<HandRecord>, ParseHandError> {
+    let mut hands = vec![];
+    let mut current = String::new();
+    for line in text.lines() {
+        if line.trim_start().starts_with("PokerStars ") && !current.trim().is_empty() {
+            hands.push(parse_hand(&current)?);
+            current.clear();
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.trim().is_empty() {
+        hands.push(parse_hand(&current)?);
+    }
+    Ok(hands)
+}
+
+impl FromStr for HandRecord {
+    type Err = ParseHandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_hand(s)
+    }
+}
+
+/// A point in a hand where a player chose an action
+#[derive(Clone)]
+pub struct Decision {
+    /// The state just before the action, holding every hole card the record knows
+    pub state: PokerState,
+    pub player: usize,
+    pub legal_actions: LegalActions,
+    pub action: PokerAction,
+}
+
+impl HandRecord {
+    /// Replays the hand, returning the state before each player's action and the action taken.
+    ///
+    /// Fails if the amounts don't add up or an event names a player without a seat.
+    pub fn decisions(&self) -> Result<Vec<Decision>, ParseHandError> {
+        let small_blind_posted = self.history.iter().any(|entry| {
+            matches!(
+                entry.event,
+                HandEvent::PostBlind {
+                    blind: Blind::Small,
+                    ..
+                }
+            )
+        });
+        let mut state = PokerState {
+            pot: 0,
+            pots: vec![],
+            player_stacks: self.starting_stacks.clone(),
+            player_contributions: HashMap::new(),
+            community_cards: vec![],
+            player_cards: HashMap::new(),
+            last_action: HashMap::new(),
+            required_call: 0,
+            history: vec![],
+            button: self.button,
+            positions: assign_positions(&self.seats_from_button(), small_blind_posted),
+        };
+        let mut street_bets = HashMap::<usize, u64>::new();
+        let mut last_raise = 0;
+        let mut bets = 0;
+        // Players who acted since the last full raise, and those a short all-in left only able
+        // to call or fold, as in the engine
+        let mut acted = HashSet::<usize>::new();
+        let mut raising_closed = HashSet::<usize>::new();
+        let mut decisions = vec![];
+
+        fn stack(state: &PokerState, player: usize) -> Result<u64, ParseHandError> {
+            state.player_stacks.get(&player).copied().ok_or_else(|| {
+                ParseHandError::InvalidHistory(format!("player {} has no seat", player))
+            })
+        }
+
+        fn commit(
+            state: &mut PokerState,
+            player: usize,
+            amount: u64,
+        ) -> Result<(), ParseHandError> {
+            let remaining = stack(state, player)?.checked_sub(amount).ok_or_else(|| {
+                ParseHandError::InvalidHistory(format!(
+                    "player {} puts in {} with a stack of {}",
+                    player, amount, state.player_stacks[&player]
+                ))
+            })?;
+            state.player_stacks.insert(player, remaining);
+            *state.player_contributions.entry(player).or_insert(0) += amount;
+            state.pot += amount;
+            Ok(())
+        }
+
+        for entry in &self.history {
+            match &entry.event {
+                HandEvent::HoleCards { player, cards } => {
+                    state.player_cards.insert(*player, *cards);
+                }
+                HandEvent::PostAnte { player, amount } => commit(&mut state, *player, *amount)?,
+                HandEvent::PostBlind { player, amount, .. } => {
+                    state.last_action.insert(
+                        *player,
+                        PokerAction::Raise(amount.saturating_sub(state.required_call)),
+                    );
+                    commit(&mut state, *player, *amount)?;
+                    let total = street_bets.entry(*player).or_insert(0);
+                    *total += amount;
+                    state.required_call = state.required_call.max(*total);
+                    // The blinds open the betting, as in the engine
+                    last_raise = state.required_call;
+                    bets = blind_bets(state.required_call, self.big_blind);
+                }
+                HandEvent::Action {
+                    player,
+                    action,
+                    amount,
+                    ..
+                } => {
+                    let to_call = state
+                        .required_call
+                        .checked_sub(*street_bets.get(player).unwrap_or(&0))
+                        .ok_or_else(|| {
+                            ParseHandError::InvalidHistory(format!(
+                                "player {} has bet more than the current bet",
+                                player
+                            ))
+                        })?;
+                    let stack = stack(&state, *player)?;
+                    let raise_range = if raising_closed.contains(player) {
+                        None
+                    } else {
+                        self.betting_structure.raise_bounds(&BettingSituation {
+                            pot: state.pot,
+                            to_call,
+                            stack,
+                            last_raise,
+                            big_blind: self.big_blind,
+                            big_bet_street: state.community_cards.len() > 3,
+                            bets,
+                        })
+                    };
+                    decisions.push(Decision {
+                        state: state.clone(),
+                        player: *player,
+                        legal_actions: LegalActions::new(to_call, stack, raise_range),
+                        action: action.clone(),
+                    });
+
+                    commit(&mut state, *player, *amount)?;
+                    let total = street_bets.entry(*player).or_insert(0);
+                    *total += amount;
+                    if *total > state.required_call {
+                        let raise = *total - state.required_call;
+                        state.required_call = *total;
+                        if raise >= last_raise {
+                            last_raise = raise;
+                            bets += 1;
+                            acted.clear();
+                            raising_closed.clear();
+                        } else {
+                            raising_closed.extend(acted.iter().copied());
+                        }
+                    }
+                    acted.insert(*player);
+                    state.last_action.insert(*player, action.clone());
+                }
+                HandEvent::CommunityCards { cards } => {
+                    state.community_cards.extend(cards);
+                    street_bets.clear();
+                    state.required_call = 0;
+                    last_raise = 0;
+                    bets = 0;
+                    acted.clear();
+                    raising_closed.clear();
+                    let folded = state
+                        .last_action
+                        .iter()
+                        .filter(|(_, action)| **action == PokerAction::Fold)
+                        .map(|(player, _)| *player)
+                        .collect();
+                    state.pots = build_pots(&state.player_contributions, &folded);
+                }
+                HandEvent::UncalledBetReturned { player, amount } => {
+                    let contributed = state.player_contributions.get(player).copied();
+                    let (Some(contributed), Some(pot)) = (
+                        contributed.and_then(|bet| bet.checked_sub(*amount)),
+                        state.pot.checked_sub(*amount),
+                    ) else {
+                        return Err(ParseHandError::InvalidHistory(format!(
+                            "{} returned to player {} was never bet",
+                            amount, player
+                        )));
+                    };
+                    let stack = stack(&state, *player)?;
+                    state.player_stacks.insert(*player, stack + amount);
+                    state.player_contributions.insert(*player, contributed);
+                    state.pot = pot;
+                }
+                HandEvent::WinsPot { player, amount, .. } => {
+                    let stack = stack(&state, *player)?;
+                    state.player_stacks.insert(*player, stack + amount);
+                }
+            }
+            state.history.push(entry.clone());
+        }
+        Ok(decisions)
+    }
+}
@@ -0,0 +1,240 @@
+use crate::card::{Card, CardSet};
+use crate::evaluator::{encode_hand_rank, evaluate_7};
+use crate::hand_utils::evaluate_hand;
+use crate::range::{Combo, Range};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::Display;
+
+/// The holdings hand strength is measured against
+#[derive(Debug, Clone, Copy)]
+pub enum Opponent<'a> {
+    /// Every hand the opponent could hold, equally likely
+    Random,
+    /// The combos of a range, weighted as in the range
+    Range(&'a Range),
+}
+
+/// How far ahead hand potential looks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookahead {
+    /// The next card only
+    NextStreet,
+    /// Every card up to the river
+    River,
+}
+
+/// The chance of the hand's standing changing over the cards to come
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HandPotential {
+    /// PPot, the chance of ending ahead when behind now, ties counting half
+    pub positive: f64,
+    /// NPot, the chance of ending behind when ahead now, ties counting half
+    pub negative: f64,
+}
+
+/// Why a hand strength metric couldn't be computed
+#[derive(Debug, Clone, PartialEq)]
+pub enum HandStrengthError {
+    /// The board must have from three to five cards
+    InvalidBoard(usize),
+    /// A card is used more than once across the hole cards and board
+    DuplicateCard(Card),
+    /// Every combo the opponent could hold is blocked
+    NoOpponentHands,
+}
+
+impl Display for HandStrengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandStrengthError::InvalidBoard(count) => {
+                write!(f, "a board needs 3 to 5 cards, got {}", count)
+            }
+            HandStrengthError::DuplicateCard(card) => write!(f, "{} is used more than once", card),
+            HandStrengthError::NoOpponentHands => write!(f, "the opponent has no possible hands"),
+        }
+    }
+}
+
+impl Error for HandStrengthError {}
+
+/// Whether a hand is ahead, tied or behind, used to index potential tables
+const AHEAD: usize = 0;
+const TIED: usize = 1;
+const BEHIND: usize = 2;
+
+/// Compares the values of two hands, from the first hand's point of view
+fn standing(ours: u32, theirs: u32) -> usize {
+    match ours.cmp(&theirs) {
+        Ordering::Greater => AHEAD,
+        Ordering::Equal => TIED,
+        Ordering::Less => BEHIND,
+    }
+}
+
+/// The value of hole cards on a board of three to five cards
+fn hand_value(hole: (Card, Card), board: &[Card]) -> u32 {
+    let mut cards = [hole.0; 7];
+    cards[1] = hole.1;
+    cards[2..2 + board.len()].copy_from_slice(board);
+    match board.len() {
+        5 => evaluate_7(&cards),
+        // Short of seven cards there's no lookup table, so the hand is valued without logging
+        count => encode_hand_rank(&evaluate_hand(&cards[..2 + count])),
+    }
+}
+
+/// Checks the hole cards and board, returning the cards they use
+fn known_cards(hole: (Card, Card), board: &[Card]) -> Result<CardSet, HandStrengthError> {
+    if board.len() < 3 || board.len() > 5 {
+        return Err(HandStrengthError::InvalidBoard(board.len()));
+    }
+    let mut known = CardSet::new();
+    for card in [hole.0, hole.1].iter().chain(board) {
+        if !known.insert(*card) {
+            return Err(HandStrengthError::DuplicateCard(*card));
+        }
+    }
+    Ok(known)
+}
+
+/// The opponent's combos that don't use any known card, with their weights
+fn opponent_hands(opponent: Opponent, known: &CardSet) -> Result<Vec<Combo>, HandStrengthError> {
+    let hands: Vec<Combo> = match opponent {
+        Opponent::Random => {
+            let cards = (CardSet::full() - *known).to_vec();
+            cards
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, first)| {
+                    cards[idx + 1..].iter().map(move |second| Combo {
+                        cards: (*first, *second),
+                        weight: 1.0,
+                    })
+                })
+                .collect()
+        }
+        Opponent::Range(range) => range.without(known).combos(),
+    };
+    if hands.is_empty() {
+        return Err(HandStrengthError::NoOpponentHands);
+    }
+    Ok(hands)
+}
+
+/// Immediate hand strength (HS): the chance the hole cards are ahead of the opponent on the
+/// current board, ties counting half
+pub fn hand_strength(
+    hole: (Card, Card),
+    board: &[Card],
+    opponent: Opponent,
+) -> Result<f64, HandStrengthError> {
+    let known = known_cards(hole, board)?;
+    let ours = hand_value(hole, board);
+    let mut totals = [0.0; 3];
+    for combo in opponent_hands(opponent, &known)? {
+        totals[standing(ours, hand_value(combo.cards, board))] += combo.weight;
+    }
+    Ok((totals[AHEAD] + totals[TIED] / 2.0) / totals.iter().sum::<f64>())
+}
+
+/// Positive and negative hand potential (PPot and NPot) over the cards still to come.
+///
+/// Both are zero on the river, where no cards are left to change the hand's standing.
+pub fn hand_potential(
+    hole: (Card, Card),
+    board: &[Card],
+    opponent: Opponent,
+    lookahead: Lookahead,
+) -> Result<HandPotential, HandStrengthError> {
+    let known = known_cards(hole, board)?;
+    let to_come = match lookahead {
+        Lookahead::NextStreet => (5 - board.len()).min(1),
+        Lookahead::River => 5 - board.len(),
+    };
+    let ours = hand_value(hole, board);
+    // Our value on a runout doesn't depend on the opponent, so it's worked out once per runout,
+    // keyed by the indices of the cards to come
+    let runout_key = |runout: &[Card]| {
+        runout[board.len()..]
+            .iter()
+            .fold(0, |key, card| key * 52 + card.index())
+    };
+    let mut our_values = vec![0; 52_usize.pow(to_come as u32)];
+    let mut future = board.to_vec();
+    let unknown = (CardSet::full() - known).to_vec();
+    deal_runouts(&unknown, to_come, 0, &mut future, &mut |runout| {
+        our_values[runout_key(runout)] = hand_value(hole, runout);
+    });
+    // `transitions[now][later]` weights the runouts by standing now and after the cards to come
+    let mut transitions = [[0.0; 3]; 3];
+    for combo in opponent_hands(opponent, &known)? {
+        let hand = combo.cards;
+        let now = standing(ours, hand_value(hand, board));
+        let unseen = (CardSet::full() - known - CardSet::from_iter([hand.0, hand.1])).to_vec();
+        let mut later = [0.0; 3];
+        let mut runouts = 0;
+        deal_runouts(&unseen, to_come, 0, &mut future, &mut |runout| {
+            later[standing(our_values[runout_key(runout)], hand_value(hand, runout))] += 1.0;
+            runouts += 1;
+        });
+        for (standing, count) in later.iter().enumerate() {
+            transitions[now][standing] += combo.weight * count / runouts as f64;
+        }
+    }
+
+    let total = |now: usize| transitions[now].iter().sum::<f64>();
+    let ratio = |numerator: f64, denominator: f64| {
+        if denominator > 0.0 {
+            numerator / denominator
+        } else {
+            0.0
+        }
+    };
+    Ok(HandPotential {
+        positive: ratio(
+            transitions[BEHIND][AHEAD]
+                + transitions[BEHIND][TIED] / 2.0
+                + transitions[TIED][AHEAD] / 2.0,
+            total(BEHIND) + total(TIED) / 2.0,
+        ),
+        negative: ratio(
+            transitions[AHEAD][BEHIND]
+                + transitions[TIED][BEHIND] / 2.0
+                + transitions[AHEAD][TIED] / 2.0,
+            total(AHEAD) + total(TIED) / 2.0,
+        ),
+    })
+}
+
+/// Visits every board made by adding `count` of the cards from `start` onwards
+fn deal_runouts(
+    cards: &[Card],
+    count: usize,
+    start: usize,
+    board: &mut Vec<Card>,
+    visit: &mut dyn FnMut(&[Card]),
+) {
+    if count == 0 {
+        visit(board);
+        return;
+    }
+    for idx in start..cards.len() {
+        board.push(cards[idx]);
+        deal_runouts(cards, count - 1, idx + 1, board, visit);
+        board.pop();
+    }
+}
+
+/// Effective hand strength (EHS): the chance of being ahead now, or of drawing ahead when
+/// behind, `HS + (1 - HS) * PPot`
+pub fn effective_hand_strength(
+    hole: (Card, Card),
+    board: &[Card],
+    opponent: Opponent,
+    lookahead: Lookahead,
+) -> Result<f64, HandStrengthError> {
+    let strength = hand_strength(hole, board, opponent)?;
+    let potential = hand_potential(hole, board, opponent, lookahead)?;
+    Ok(strength + (1.0 - strength) * potential.positive)
+}
@@ -1,7 +1,67 @@
-use crate::card::{Card, Deck, Rank, Suit};
+use crate::card::{Card, Rank, Suit};
+use std::cmp::Ordering;
 use std::collections::hash_map::HashMap;
 
-fn get_rank_counts(cards: &Vec<Card>) -> HashMap<Rank, i64> {
+/// The category of a poker hand, ordered from weakest to strongest
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandCategory {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+/// The value of a poker hand.
+///
+/// Hands are ordered by category and then by their kicker ranks. The cards making up the hand
+/// are kept for display but do not take part in comparisons.
+#[derive(Clone, Debug)]
+pub struct HandRank {
+    pub category: HandCategory,
+    /// The ranks that decide between hands of the same category, most significant first
+    pub kickers: Vec<Rank>,
+    /// The five cards that make the hand
+    pub cards: Vec<Card>,
+}
+
+impl HandRank {
+    fn new(category: HandCategory, kickers: Vec<Rank>, cards: Vec<Card>) -> Self {
+        HandRank {
+            category,
+            kickers,
+            cards,
+        }
+    }
+}
+
+impl PartialEq for HandRank {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HandRank {}
+
+impl PartialOrd for HandRank {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HandRank {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.category
+            .cmp(&other.category)
+            .then_with(|| self.kickers.cmp(&other.kickers))
+    }
+}
+
+fn get_rank_counts(cards: &[Card]) -> HashMap<Rank, i64> {
     cards
         .iter()
         .fold(HashMap::<Rank, i64>::new(), |mut acc, x| {
@@ -10,7 +70,7 @@ fn get_rank_counts(cards: &Vec<Card>) -> HashMap<Rank, i64> {
         })
 }
 
-fn get_highest_n_of_kind(cards: &Vec<Card>, n: i64) -> Rank {
+fn get_highest_n_of_kind(cards: &[Card], n: i64) -> Rank {
     let rank_counts = get_rank_counts(cards);
     rank_counts.iter().fold(
         Rank::Null,
@@ -18,105 +78,126 @@ fn get_highest_n_of_kind(cards: &Vec<Card>, n: i64) -> Rank {
     )
 }
 
-fn straight_flush_value(cards: &Vec<Card>) -> i64 {
-    if cards.len() < 5 {
-        return -1;
-    }
+fn cards_of_rank(cards: &[Card], rank: Rank) -> Vec<Card> {
+    cards.iter().filter(|a| a.rank == rank).copied().collect()
+}
 
-    let mut cards_sorted = cards.clone();
+fn sorted_by_rank(cards: &[Card]) -> Vec<Card> {
+    let mut cards_sorted = cards.to_vec();
     cards_sorted.sort_by(|a, b| b.rank.partial_cmp(&a.rank).unwrap());
-    // Ace can be high or low
-    if cards_sorted[0].rank == Rank::Ace {
-        cards_sorted.push(cards_sorted[0].clone());
+    cards_sorted
+}
+
+/// The highest cards not of the excluded ranks, used to fill a hand out to five cards
+fn top_kickers(cards: &[Card], excluded: &[Rank], n: usize) -> Vec<Card> {
+    sorted_by_rank(cards)
+        .into_iter()
+        .filter(|a| !excluded.contains(&a.rank))
+        .take(n)
+        .collect()
+}
+
+fn straight_flush_value(cards: &[Card]) -> Option<HandRank> {
+    if cards.len() < 5 {
+        return None;
     }
-    for i in 0..cards_sorted.len() - 4 {
-        let view = &cards_sorted[i..i + 5];
-        let mut straight = true;
-        let mut flush = true;
-        for (prev_card, card) in view.iter().zip(view[1..].iter()) {
-            if (card.rank as i64) - (prev_card.rank as i64) != -1
-                && !(prev_card.rank == Rank::Two && card.rank == Rank::Ace)
-            {
-                straight = false;
-                break;
-            } else if card.suit != prev_card.suit {
-                flush = false;
-                break;
-            }
-        }
-        if straight && flush {
-            return view[0].rank as i64;
-        }
+
+    let mut suit_counts = HashMap::<Suit, Vec<Card>>::new();
+    for card in cards {
+        suit_counts.entry(card.suit).or_default().push(*card);
     }
-    -1
+    // Only the cards of a flush suit can make a straight flush
+    suit_counts
+        .values()
+        .filter_map(|hand| straight_value(hand))
+        .max()
+        .map(|straight| {
+            HandRank::new(
+                HandCategory::StraightFlush,
+                straight.kickers,
+                straight.cards,
+            )
+        })
 }
 
-fn quads_value(cards: &Vec<Card>) -> i64 {
-    let values: HashMap<Rank, i64> = get_rank_counts(cards);
+fn quads_value(cards: &[Card]) -> Option<HandRank> {
     let quads = get_highest_n_of_kind(cards, 4);
-    let top_card: Rank = cards
-        .clone()
-        .iter()
-        .filter(|a| a.rank != quads)
-        .max_by(|a, b| a.rank.partial_cmp(&b.rank).unwrap())
-        .unwrap()
-        .rank;
-
     if quads == Rank::Null {
-        -1
-    } else {
-        (quads as i64) * 13 + top_card as i64
+        return None;
     }
+
+    let mut hand = cards_of_rank(cards, quads);
+    let mut kickers = vec![quads];
+    for card in top_kickers(cards, &[quads], 1) {
+        kickers.push(card.rank);
+        hand.push(card);
+    }
+    Some(HandRank::new(HandCategory::FourOfAKind, kickers, hand))
 }
 
-fn full_house_value(cards: &Vec<Card>) -> i64 {
+fn full_house_value(cards: &[Card]) -> Option<HandRank> {
+    let rank_counts = get_rank_counts(cards);
     let highest_three = get_highest_n_of_kind(cards, 3);
-    let highest_two = get_highest_n_of_kind(cards, 2);
-    if highest_three == Rank::Null || highest_two == Rank::Null {
-        -1
-    } else {
-        13 * (highest_three as i64) + (highest_two as i64)
+    if highest_three == Rank::Null {
+        return None;
     }
+    // A second set counts as the pair
+    let highest_two = rank_counts
+        .iter()
+        .filter(|(rank, count)| **rank != highest_three && **count >= 2)
+        .map(|(rank, _)| *rank)
+        .max();
+    let highest_two = highest_two?;
+
+    let mut hand = cards_of_rank(cards, highest_three);
+    hand.extend(cards_of_rank(cards, highest_two).into_iter().take(2));
+    Some(HandRank::new(
+        HandCategory::FullHouse,
+        vec![highest_three, highest_two],
+        hand,
+    ))
 }
 
-fn flush_value(cards: &Vec<Card>) -> i64 {
+fn flush_value(cards: &[Card]) -> Option<HandRank> {
     if cards.len() < 5 {
-        return -1;
+        return None;
     }
     let mut suit_counts = HashMap::<Suit, Vec<Card>>::new();
     for card in cards {
-        suit_counts
-            .entry(card.suit)
-            .or_insert(vec![])
-            .push(card.clone());
-    }
-    for (_, hand) in suit_counts {
-        if hand.len() < 5 {
-            continue;
-        }
-        let mut ordered_hand = hand.clone();
-        ordered_hand.sort_by(|a, b| b.rank.partial_cmp(&a.rank).unwrap());
-        let mut value = 0;
-        for (i, card) in ordered_hand[0..5].iter().enumerate() {
-            value += 13_i64.pow(4 - i as u32) * (card.rank as i64);
-        }
-        return value;
+        suit_counts.entry(card.suit).or_default().push(*card);
     }
-    -1
+    suit_counts
+        .values()
+        .filter(|hand| hand.len() >= 5)
+        .map(|hand| {
+            let mut ordered_hand = sorted_by_rank(hand);
+            ordered_hand.truncate(5);
+            HandRank::new(
+                HandCategory::Flush,
+                ordered_hand.iter().map(|a| a.rank).collect(),
+                ordered_hand,
+            )
+        })
+        .max()
 }
 
-fn straight_value(cards: &Vec<Card>) -> i64 {
+fn straight_value(cards: &[Card]) -> Option<HandRank> {
     if cards.len() < 5 {
-        return -1;
+        return None;
     }
 
-    let mut cards_sorted = cards.clone();
-    cards_sorted.sort_by(|a, b| b.rank.partial_cmp(&a.rank).unwrap());
+    // Only one card of each rank can take part in a straight
+    let mut cards_sorted = sorted_by_rank(cards);
+    cards_sorted.dedup_by_key(|a| a.rank);
     let mut card_ranks: Vec<i64> = cards_sorted.iter().map(|a| a.rank as i64).collect();
 
     // Ace can be high or low
     if card_ranks[0] == Rank::Ace as i64 {
         card_ranks.push(-1);
+        cards_sorted.push(cards_sorted[0]);
+    }
+    if card_ranks.len() < 5 {
+        return None;
     }
     for i in 0..card_ranks.len() - 4 {
         let view = &card_ranks[i..i + 5];
@@ -128,86 +209,87 @@ fn straight_value(cards: &Vec<Card>) -> i64 {
             }
         }
         if straight {
-            return view[0];
+            return Some(HandRank::new(
+                HandCategory::Straight,
+                vec![Rank::from(view[0])],
+                cards_sorted[i..i + 5].to_vec(),
+            ));
         }
     }
-    -1
+    None
 }
 
-fn set_value(cards: &Vec<Card>) -> i64 {
+fn set_value(cards: &[Card]) -> Option<HandRank> {
     let set = get_highest_n_of_kind(cards, 3);
     if set == Rank::Null {
-        return -1;
+        return None;
     }
-    let mut total = set as i64 * 13_i64.pow(2);
-
-    let mut cards_sorted = cards.clone();
-    cards_sorted.sort_by(|a, b| b.rank.partial_cmp(&a.rank).unwrap());
-    for (idx, card) in cards[0..2].iter().enumerate() {
-        if card.rank == set {
-            continue;
-        } else {
-            total += 13_i64.pow(1 - idx as u32) * card.rank as i64;
-        }
+    let mut hand = cards_of_rank(cards, set);
+    let mut kickers = vec![set];
+    for card in top_kickers(cards, &[set], 2) {
+        kickers.push(card.rank);
+        hand.push(card);
     }
-    total
+    Some(HandRank::new(HandCategory::ThreeOfAKind, kickers, hand))
 }
 
-fn two_pair_value(cards: &Vec<Card>) -> i64 {
+fn two_pair_value(cards: &[Card]) -> Option<HandRank> {
     let values: HashMap<Rank, i64> = get_rank_counts(cards);
     let (mut pairs, _): (Vec<&Rank>, Vec<&i64>) =
         values.iter().filter(|(_, count)| **count == 2).unzip();
     if pairs.len() < 2 {
-        return -1;
+        return None;
     }
     pairs.sort();
     pairs.reverse();
-    let top_card: Rank = cards
-        .clone()
-        .iter()
-        .filter(|a| a.rank != *pairs[0] && a.rank != *pairs[1])
-        .max_by(|a, b| a.rank.partial_cmp(&b.rank).unwrap())
-        .unwrap()
-        .rank;
 
-    if pairs.len() < 2 {
-        -1
-    } else {
-        (*pairs[0] as i64) * 13 * 13 + (*pairs[1] as i64) * 13 + top_card as i64
+    let mut hand = cards_of_rank(cards, *pairs[0]);
+    hand.append(&mut cards_of_rank(cards, *pairs[1]));
+    let mut kickers = vec![*pairs[0], *pairs[1]];
+    // A third pair can play as the kicker
+    for card in top_kickers(cards, &[*pairs[0], *pairs[1]], 1) {
+        kickers.push(card.rank);
+        hand.push(card);
     }
+    Some(HandRank::new(HandCategory::TwoPair, kickers, hand))
 }
 
-fn pair_value(cards: &Vec<Card>) -> i64 {
+fn pair_value(cards: &[Card]) -> Option<HandRank> {
     let pair = get_highest_n_of_kind(cards, 2);
     if pair == Rank::Null {
-        return -1;
+        return None;
     }
 
-    let mut cards_sorted = cards.clone();
-    cards_sorted.sort_by(|a, b| b.rank.partial_cmp(&a.rank).unwrap());
-    let mut total = 13_i64.pow(3) * pair as i64;
-    for (idx, card) in cards[0..3].iter().enumerate() {
-        if card.rank == pair {
-            continue;
-        } else {
-            total += 13_i64.pow(2 - idx as u32) * card.rank as i64
-        }
+    let mut hand = cards_of_rank(cards, pair);
+    let mut kickers = vec![pair];
+    for card in top_kickers(cards, &[pair], 3) {
+        kickers.push(card.rank);
+        hand.push(card);
     }
-    total
+    Some(HandRank::new(HandCategory::Pair, kickers, hand))
 }
 
-fn high_card_value(cards: &Vec<Card>) -> i64 {
-    let mut cards_sorted = cards.clone();
-    cards_sorted.sort_by(|a, b| b.rank.partial_cmp(&a.rank).unwrap());
-    let mut total = 0;
-    for (idx, card) in cards_sorted[0..5].iter().enumerate() {
-        total += 13_i64.pow(4 - idx as u32) * card.rank as i64;
-    }
-    return total;
+fn high_card_value(cards: &[Card]) -> Option<HandRank> {
+    let mut cards_sorted = sorted_by_rank(cards);
+    cards_sorted.truncate(5);
+    Some(HandRank::new(
+        HandCategory::HighCard,
+        cards_sorted.iter().map(|a| a.rank).collect(),
+        cards_sorted,
+    ))
+}
+
+type Valuation = fn(&[Card]) -> Option<HandRank>;
+
+/// Evaluates the best poker hand that can be made from at least five cards
+pub fn get_hand_value(cards: &[Card]) -> HandRank {
+    let value = evaluate_hand(cards);
+    log::trace!(category:? = value.category, kickers:? = value.kickers; "evaluated hand");
+    value
 }
 
-pub fn get_hand_value(cards: &Vec<Card>) -> i64 {
-    let valuations: Vec<fn(&Vec<Card>) -> i64> = vec![
+pub(crate) fn evaluate_hand(cards: &[Card]) -> HandRank {
+    let valuations: Vec<Valuation> = vec![
         straight_flush_value,
         quads_value,
         full_house_value,
@@ -218,13 +300,20 @@ pub fn get_hand_value(cards: &Vec<Card>) -> i64 {
         pair_value,
         high_card_value,
     ];
-    let base = 13_i64.pow(5);
-    for (idx, val) in valuations.iter().enumerate() {
-        let value = val(cards);
-        if value != -1 {
-            println!("{:?}", idx);
-            return base * (valuations.len() - 1 - idx) as i64 + value;
+    for val in valuations.iter() {
+        if let Some(value) = val(cards) {
+            return value;
         }
     }
-    -1
+    unreachable!("high_card_value always values a hand")
+}
+
+/// Picks the best five card hand out of five, six or seven cards.
+///
+/// Returns `None` if the number of cards can't be dealt in hold'em.
+pub fn get_best_hand(cards: &[Card]) -> Option<HandRank> {
+    if !(5..=7).contains(&cards.len()) {
+        return None;
+    }
+    Some(get_hand_value(cards))
 }
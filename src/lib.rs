@@ -1,4 +1,9 @@
 pub mod card;
+pub mod equity;
+pub mod evaluator;
 pub mod game;
+pub mod hand_history;
+pub mod hand_strength;
 pub mod hand_utils;
 pub mod poker;
+pub mod range;
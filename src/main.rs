@@ -1,13 +1,17 @@
-use poker::card::{Card, Rank, Suit};
 use poker::game::{GameEngine, GamePlayer};
-use poker::hand_utils::*;
-use poker::new_card;
-use poker::poker::{PassivePokerPlayer, PokerAction, PokerEngine, PokerState};
+use poker::poker::{
+    ConsoleObserver, LegalActions, PassivePokerPlayer, PlayerObservation, PokerAction, PokerEngine,
+    TableConfig,
+};
 
 fn main() {
-    let players: Vec<&dyn GamePlayer<PokerState, PokerAction>> =
+    let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
         vec![&PassivePokerPlayer {}, &PassivePokerPlayer {}];
 
-    let mut engine = PokerEngine::new(&players);
-    engine.run();
+    let console = ConsoleObserver {};
+    let mut engine = PokerEngine::new(&players, TableConfig::default());
+    engine.add_observer(&console);
+    if let Err(error) = engine.run() {
+        eprintln!("{}", error);
+    }
 }
@@ -1,9 +1,14 @@
-use crate::card::{Card, Deck, Rank, Suit};
+use crate::card::{Card, Deck};
 use crate::game::{GameEngine, GamePlayer};
-use crate::hand_utils::get_hand_value;
+use crate::hand_history::HandRecord;
+use crate::hand_utils::{get_hand_value, HandRank};
+use log::{debug, trace, warn};
 
 use std::collections::hash_map::HashMap;
+use std::collections::HashSet;
+use std::error::Error;
 use std::fmt::Display;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum PokerAction {
@@ -13,247 +18,1406 @@ pub enum PokerAction {
     Fold,
 }
 
+/// Why an action returned by a player was rejected
+#[derive(Debug, PartialEq, Clone)]
+pub enum IllegalActionReason {
+    /// The kind of action wasn't one of the available actions, such as checking facing a bet
+    NotAvailable,
+    RaiseTooSmall {
+        min: u64,
+    },
+    RaiseTooLarge {
+        max: u64,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PokerError {
+    IllegalAction {
+        player: usize,
+        action: PokerAction,
+        reason: IllegalActionReason,
+    },
+    /// The deck ran out of cards, as it does with more than 22 players at the table
+    DeckExhausted,
+}
+
+impl Display for PokerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PokerError::IllegalAction {
+                player,
+                action,
+                reason,
+            } => {
+                write!(f, "Player {} made illegal action {:?}: ", player, action)?;
+                match reason {
+                    IllegalActionReason::NotAvailable => write!(f, "action not available"),
+                    IllegalActionReason::RaiseTooSmall { min } => {
+                        write!(f, "raise is below the minimum of {}", min)
+                    }
+                    IllegalActionReason::RaiseTooLarge { max } => {
+                        write!(f, "raise is above the maximum of {}", max)
+                    }
+                }
+            }
+            PokerError::DeckExhausted => write!(f, "the deck ran out of cards"),
+        }
+    }
+}
+
+impl Error for PokerError {}
+
+/// What the engine does when a player returns an illegal action
+#[derive(Debug, PartialEq, Clone)]
+pub enum IllegalActionPolicy {
+    /// Ask the player again, folding them once they have made `max_attempts` illegal actions
+    Reask { max_attempts: u32 },
+    /// Fold the player, or check for them if there is nothing to call
+    Fold,
+    /// Abandon the hand, returning every chip bet in it, and return the error
+    Abort,
+}
+
+/// The actions open to the player whose turn it is.
+///
+/// Raise amounts are on top of calling, so `PokerAction::Raise(n)` puts in the call amount
+/// plus `n` chips.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LegalActions {
+    pub can_fold: bool,
+    pub can_check: bool,
+    /// The chips needed to call when facing a bet, capped at the player's stack
+    pub call_amount: Option<u64>,
+    /// The smallest and largest amounts the player may raise by, if they may raise
+    pub raise_range: Option<(u64, u64)>,
+    /// The chips the player has behind, all of which go in if they go all-in
+    pub all_in_amount: u64,
+}
+
+impl LegalActions {
+    pub fn new(to_call: u64, stack: u64, raise_range: Option<(u64, u64)>) -> Self {
+        LegalActions {
+            can_fold: to_call > 0,
+            can_check: to_call == 0,
+            call_amount: if to_call > 0 {
+                Some(to_call.min(stack))
+            } else {
+                None
+            },
+            raise_range,
+            all_in_amount: stack,
+        }
+    }
+
+    pub fn min_raise(&self) -> Option<u64> {
+        self.raise_range.map(|(min, _)| min)
+    }
+
+    pub fn max_raise(&self) -> Option<u64> {
+        self.raise_range.map(|(_, max)| max)
+    }
+
+    /// Checks an action against the legal actions, describing why it isn't allowed
+    pub fn validate(&self, action: &PokerAction) -> Result<(), IllegalActionReason> {
+        match (action, self.raise_range) {
+            (PokerAction::Fold, _) if self.can_fold => Ok(()),
+            (PokerAction::Check, _) if self.can_check => Ok(()),
+            (PokerAction::Call, _) if self.call_amount.is_some() => Ok(()),
+            (PokerAction::Raise(value), Some((min, _))) if *value < min => {
+                Err(IllegalActionReason::RaiseTooSmall { min })
+            }
+            (PokerAction::Raise(value), Some((_, max))) if *value > max => {
+                Err(IllegalActionReason::RaiseTooLarge { max })
+            }
+            (PokerAction::Raise(_), Some(_)) => Ok(()),
+            _ => Err(IllegalActionReason::NotAvailable),
+        }
+    }
+
+    pub fn is_legal(&self, action: &PokerAction) -> bool {
+        self.validate(action).is_ok()
+    }
+}
+
+/// A pot and the players who are eligible to win it
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pot {
+    pub amount: u64,
+    pub eligible_players: Vec<usize>,
+}
+
+/// Splits the chips each player has put in this hand into a main pot followed by side pots.
+///
+/// Each pot is capped at the contribution of an all-in player, so a player is only eligible
+/// for the pots they have fully matched. Folded players contribute chips but are never eligible.
+pub fn build_pots(contributions: &HashMap<usize, u64>, folded: &HashSet<usize>) -> Vec<Pot> {
+    let mut levels: Vec<u64> = contributions
+        .iter()
+        .filter(|(player, amount)| !folded.contains(player) && **amount > 0)
+        .map(|(_, amount)| *amount)
+        .collect();
+    levels.sort();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = vec![];
+    let mut previous_level = 0;
+    for level in levels {
+        let amount = contributions
+            .values()
+            .map(|c| (*c).min(level) - (*c).min(previous_level))
+            .sum();
+        let mut eligible_players: Vec<usize> = contributions
+            .iter()
+            .filter(|(player, c)| !folded.contains(player) && **c >= level)
+            .map(|(player, _)| *player)
+            .collect();
+        eligible_players.sort();
+        pots.push(Pot {
+            amount,
+            eligible_players,
+        });
+        previous_level = level;
+    }
+
+    // Chips from folded players above the highest live contribution go to the last pot
+    let excess: u64 = contributions
+        .values()
+        .map(|c| c.saturating_sub(previous_level))
+        .sum();
+    if excess > 0 {
+        if let Some(pot) = pots.last_mut() {
+            pot.amount += excess;
+        }
+    }
+    pots
+}
+
+/// The betting rounds of a hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Blind {
+    Small,
+    Big,
+    Straddle,
+}
+
+/// Something that happened during a hand. Amounts are the chips that actually moved, so an
+/// all-in for less than the full amount records what the player had.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HandEvent {
+    /// An ante or big blind ante, posted as dead money
+    PostAnte {
+        player: usize,
+        amount: u64,
+    },
+    PostBlind {
+        player: usize,
+        blind: Blind,
+        amount: u64,
+    },
+    HoleCards {
+        player: usize,
+        cards: (Card, Card),
+    },
+    /// A player's action and the chips it put into the pot
+    Action {
+        player: usize,
+        action: PokerAction,
+        amount: u64,
+        all_in: bool,
+    },
+    CommunityCards {
+        cards: Vec<Card>,
+    },
+    UncalledBetReturned {
+        player: usize,
+        amount: u64,
+    },
+    /// A player winning all or part of a pot at showdown
+    WinsPot {
+        player: usize,
+        pot: usize,
+        amount: u64,
+    },
+}
+
+/// A player's seat relative to the button.
+///
+/// Seats between the big blind and the lojack are counted from under the gun, so
+/// `UnderTheGun(1)` is UTG+1. Six-handed and up the first seat after the big blind is UTG.
+/// Heads-up the button posts the small blind and is only given the `Button` position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Position {
+    SmallBlind,
+    BigBlind,
+    UnderTheGun(usize),
+    Lojack,
+    Hijack,
+    Cutoff,
+    Button,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Position::SmallBlind => write!(f, "SB"),
+            Position::BigBlind => write!(f, "BB"),
+            Position::UnderTheGun(0) => write!(f, "UTG"),
+            Position::UnderTheGun(seat) => write!(f, "UTG+{}", seat),
+            Position::Lojack => write!(f, "LJ"),
+            Position::Hijack => write!(f, "HJ"),
+            Position::Cutoff => write!(f, "CO"),
+            Position::Button => write!(f, "BTN"),
+        }
+    }
+}
+
+/// Assigns positions to the players dealt into a hand, listed in seat order from the first
+/// seat after the button. `small_blind` is false when the small blind is dead.
+pub(crate) fn assign_positions(players: &[usize], small_blind: bool) -> HashMap<usize, Position> {
+    let mut positions = HashMap::new();
+    if players.len() == 2 {
+        positions.insert(players[0], Position::BigBlind);
+        positions.insert(players[1], Position::Button);
+        return positions;
+    }
+
+    let mut blinds = vec![Position::BigBlind];
+    if small_blind {
+        blinds.insert(0, Position::SmallBlind);
+    }
+    let (blind_players, others) = players.split_at(blinds.len().min(players.len()));
+    positions.extend(blind_players.iter().copied().zip(blinds));
+
+    // Late positions are named back from the button, everyone else counts from under the gun
+    let late = [
+        Position::Button,
+        Position::Cutoff,
+        Position::Hijack,
+        Position::Lojack,
+    ];
+    let num_late = if others.len() > 3 {
+        late.len().min(others.len() - 1)
+    } else {
+        others.len()
+    };
+    let (early, late_players) = others.split_at(others.len() - num_late);
+    positions.extend(late_players.iter().rev().copied().zip(late));
+    positions.extend(
+        early
+            .iter()
+            .enumerate()
+            .map(|(idx, player)| (*player, Position::UnderTheGun(idx))),
+    );
+    positions
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub street: Street,
+    pub event: HandEvent,
+}
+
 #[derive(Clone)]
 pub struct PokerState {
     pub pot: u64,
+    pub pots: Vec<Pot>,
     pub player_stacks: HashMap<usize, u64>,
+    /// Total chips each player has put into the pot this hand
+    pub player_contributions: HashMap<usize, u64>,
     pub community_cards: Vec<Card>,
     pub player_cards: HashMap<usize, (Card, Card)>,
     pub last_action: HashMap<usize, PokerAction>,
     pub required_call: u64,
+    /// Everything that has happened in the current hand in order, or in the last hand once it
+    /// is over
+    pub history: Vec<HistoryEntry>,
+    /// The seat with the dealer button, which may be empty under the dead button rule
+    pub button: usize,
+    /// The position of every player dealt into the current or last hand
+    pub positions: HashMap<usize, Position>,
+}
+
+/// Everything one player is allowed to know about the hand in progress
+#[derive(Debug, Clone)]
+pub struct PlayerObservation {
+    pub player: usize,
+    pub hole_cards: (Card, Card),
+    pub community_cards: Vec<Card>,
+    pub pot: u64,
+    pub pots: Vec<Pot>,
+    pub player_stacks: HashMap<usize, u64>,
+    /// Total chips each player has put into the pot this hand
+    pub player_contributions: HashMap<usize, u64>,
+    pub last_action: HashMap<usize, PokerAction>,
+    pub required_call: u64,
+    /// The hand history so far, without other players' hole cards
+    pub history: Vec<HistoryEntry>,
+    pub button: usize,
+    pub positions: HashMap<usize, Position>,
+}
+
+impl PokerState {
+    /// The street being played, judged by the number of community cards dealt
+    pub fn street(&self) -> Street {
+        match self.community_cards.len() {
+            0 => Street::Preflop,
+            3 => Street::Flop,
+            4 => Street::Turn,
+            _ => Street::River,
+        }
+    }
+
+    /// The history entries of one street
+    pub fn street_history(&self, street: Street) -> impl Iterator<Item = &HistoryEntry> {
+        self.history
+            .iter()
+            .filter(move |entry| entry.street == street)
+    }
+
+    /// The part of the state visible to a player, hiding every other player's hole cards
+    pub fn observation(&self, player: usize) -> PlayerObservation {
+        PlayerObservation {
+            player,
+            hole_cards: self.player_cards[&player],
+            community_cards: self.community_cards.clone(),
+            pot: self.pot,
+            pots: self.pots.clone(),
+            player_stacks: self.player_stacks.clone(),
+            player_contributions: self.player_contributions.clone(),
+            last_action: self.last_action.clone(),
+            required_call: self.required_call,
+            history: self
+                .history
+                .iter()
+                .filter(|entry| match entry.event {
+                    HandEvent::HoleCards { player: owner, .. } => owner == player,
+                    _ => true,
+                })
+                .cloned()
+                .collect(),
+            button: self.button,
+            positions: self.positions.clone(),
+        }
+    }
 }
 
 impl Display for PokerState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (player, stack) in &self.player_stacks {
-            let res = write!(
+            writeln!(
                 f,
-                "Player {} has {} chips and last performed {:?}\n",
+                "Player {} has {} chips and last performed {:?}",
                 player,
                 stack,
-                self.last_action
-                    .get(player)
-                    .unwrap_or_else(|| &PokerAction::Check)
-            );
-            if res.is_err() {
-                return res;
-            }
+                self.last_action.get(player).unwrap_or(&PokerAction::Check)
+            )?;
         }
 
         for (player, (card1, card2)) in &self.player_cards {
-            let res = write!(f, "Player {} has {} and {}\n", player, card1, card2);
-            if res.is_err() {
-                return res;
-            }
+            writeln!(f, "Player {} has {} and {}", player, card1, card2)?;
         }
 
-        let res = write!(f, "Community cards: ");
-        if res.is_err() {
-            return res;
-        }
+        write!(f, "Community cards: ")?;
         for card in &self.community_cards[0..self.community_cards.len() - 1] {
-            let res = write!(f, "{}, ", card);
-            if res.is_err() {
-                return res;
-            }
+            write!(f, "{}, ", card)?;
         }
-        let res = write!(
+        writeln!(
             f,
-            "{}\n",
+            "{}",
             self.community_cards[self.community_cards.len() - 1]
-        );
+        )?;
+
+        write!(f, "Pot: {}", self.pot)
+    }
+}
+
+/// The rules limiting how much a player may bet or raise
+#[derive(Debug, Clone, PartialEq)]
+pub enum BettingStructure {
+    /// Any raise at least as large as the last raise, up to the player's whole stack
+    NoLimit,
+    /// As no-limit, but a raise may be no larger than the pot after calling
+    PotLimit,
+    /// Bets and raises of the small bet preflop and on the flop and of the big bet on the
+    /// turn and river
+    FixedLimit {
+        small_bet: u64,
+        big_bet: u64,
+        /// The number of bets and raises allowed each street, with the big blind counting as
+        /// the first bet preflop
+        raise_cap: u32,
+    },
+}
+
+/// The betting so far on a street, from the point of view of the player to act
+#[derive(Debug, Clone, PartialEq)]
+pub struct BettingSituation {
+    /// All chips in the middle, including bets on this street
+    pub pot: u64,
+    pub to_call: u64,
+    /// The chips the player has behind
+    pub stack: u64,
+    /// The size of the last bet or raise on this street
+    pub last_raise: u64,
+    pub big_blind: u64,
+    /// Whether this is the turn or river
+    pub big_bet_street: bool,
+    /// The number of bets and raises made so far on this street
+    pub bets: u32,
+}
+
+impl BettingStructure {
+    /// The smallest and largest amounts the player may raise by, or `None` if they can't raise.
+    ///
+    /// A player without enough chips for a full raise may still raise all-in.
+    pub fn raise_bounds(&self, situation: &BettingSituation) -> Option<(u64, u64)> {
+        if situation.stack <= situation.to_call {
+            return None;
+        }
+        let all_in = situation.stack - situation.to_call;
+        let min_raise = situation.last_raise.max(situation.big_blind);
+        let (min, max) = match self {
+            BettingStructure::NoLimit => (min_raise, all_in),
+            BettingStructure::PotLimit => (min_raise, situation.pot + situation.to_call),
+            BettingStructure::FixedLimit {
+                small_bet,
+                big_bet,
+                raise_cap,
+            } => {
+                if situation.bets >= *raise_cap {
+                    return None;
+                }
+                let bet = if situation.big_bet_street {
+                    *big_bet
+                } else {
+                    *small_bet
+                };
+                (bet, bet)
+            }
+        };
+        let max = max.min(all_in);
+        Some((min.min(max), max))
+    }
+}
+
+/// The bets the blinds count as preflop, given the largest of them. The big blind is the
+/// opening bet, and a straddle only counts as a raise when it puts in more than the big blind.
+pub(crate) fn blind_bets(required_call: u64, big_blind: u64) -> u32 {
+    if required_call > big_blind {
+        2
+    } else {
+        1
+    }
+}
 
-        if res.is_err() {
-            return res;
+/// The stakes a table is played at
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableConfig {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    /// Dead money posted by every player before the blinds
+    pub ante: u64,
+    /// Dead money posted by the big blind on behalf of the whole table
+    pub big_blind_ante: u64,
+    /// A live blind posted by the player after the big blind, who then acts last preflop. A
+    /// player with no more than the big blind behind doesn't straddle, as it couldn't raise.
+    pub straddle: Option<u64>,
+    pub starting_stack: u64,
+    pub betting_structure: BettingStructure,
+    pub illegal_action_policy: IllegalActionPolicy,
+    /// The table name written in hand histories
+    pub table_name: String,
+    /// The names written in hand histories, with `Player n` for seats not named
+    pub player_names: HashMap<usize, String>,
+    /// When the first hand starts, in seconds since the Unix epoch, with each hand after it a
+    /// minute later. `None` reads the system clock, so exports differ from run to run.
+    pub start_time: Option<u64>,
+    /// How many of the latest hand records the engine keeps, none by default. Observers are
+    /// given every hand's record either way.
+    pub hand_record_limit: usize,
+}
+
+impl Default for TableConfig {
+    fn default() -> Self {
+        TableConfig {
+            small_blind: 1,
+            big_blind: 2,
+            ante: 0,
+            big_blind_ante: 0,
+            straddle: None,
+            starting_stack: 200,
+            betting_structure: BettingStructure::NoLimit,
+            illegal_action_policy: IllegalActionPolicy::Fold,
+            table_name: "Table 1".to_string(),
+            player_names: HashMap::new(),
+            start_time: None,
+            hand_record_limit: 0,
         }
+    }
+}
 
-        write!(f, "Pot: {}", self.pot)
+/// The progress of one betting round
+struct BettingRound {
+    /// Chips each player has put in on this street
+    commitment: HashMap<usize, u64>,
+    /// The size of the largest raise this street, the least the next raise can be
+    last_raise: u64,
+    /// Bets and raises made this street, counting the blinds as the opening bet
+    bets: u32,
+    /// Players who still have to act before the round can end
+    to_act: HashSet<usize>,
+    /// Players facing a short all-in after acting, who may only call or fold
+    raising_closed: HashSet<usize>,
+}
+
+/// The seats due to post the blinds in a hand
+#[derive(Clone, Copy)]
+struct BlindSeats {
+    /// Dead if the seat is empty or its player busted
+    small_blind: usize,
+    big_blind: usize,
+}
+
+/// Watches the hands a `PokerEngine` plays. Every event does nothing by default, so an
+/// observer only implements the ones it cares about.
+///
+/// The engine holds observers by shared reference, so the caller can still read them while
+/// hands are played. An observer that keeps what it sees does so through interior mutability,
+/// such as a `RefCell` or a `Mutex`.
+pub trait PokerObserver {
+    /// The button has moved and positions are assigned, before any chips are posted
+    fn hand_started(&self, _state: &PokerState) {}
+    /// A player was dealt their hole cards
+    fn cards_dealt(&self, _player: usize, _cards: (Card, Card)) {}
+    /// A player posted an ante, when `blind` is `None`, or a blind
+    fn chips_posted(&self, _player: usize, _amount: u64, _blind: Option<Blind>) {}
+    /// A player acted, putting `amount` chips into the pot
+    fn action_taken(
+        &self,
+        _player: usize,
+        _action: &PokerAction,
+        _amount: u64,
+        _state: &PokerState,
+    ) {
     }
+    /// The community cards of a new street were dealt
+    fn street_dealt(&self, _street: Street, _cards: &[Card], _state: &PokerState) {}
+    /// The players left after the river show their hands, before the pots are paid
+    fn showdown(&self, _hands: &HashMap<usize, HandRank>, _state: &PokerState) {}
+    /// A player won `amount` chips from a pot, 0 being the main pot
+    fn pot_awarded(&self, _player: usize, _pot: usize, _amount: u64) {}
+    /// The record of a hand played to the end, for writing out as a hand history
+    fn hand_recorded(&self, _record: &HandRecord) {}
+    /// A hand was played to the end
+    fn hand_finished(&self, _state: &PokerState) {}
+    /// A player lost their last chips in the hand just finished
+    fn player_busted(&self, _player: usize) {}
 }
 
 pub struct PokerEngine<'a> {
     state: PokerState,
+    config: TableConfig,
     deck: Deck,
-    players: &'a Vec<&'a dyn GamePlayer<PokerState, PokerAction>>,
-    starting_player: usize,
+    players: &'a Vec<&'a dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>>,
+    /// The blind seats of the current or last hand, or `None` before the first hand
+    blind_seats: Option<BlindSeats>,
+    illegal_actions: Vec<PokerError>,
+    /// Hands played to the end so far, aborted hands not counting
+    hands_played: u64,
+    /// The latest hand records, up to the configured limit
+    hand_records: Vec<HandRecord>,
+    observers: Vec<&'a dyn PokerObserver>,
+}
+
+impl<'a> PokerEngine<'a> {
+    /// Registers an observer to be told about every hand played from now on
+    pub fn add_observer(&mut self, observer: &'a dyn PokerObserver) {
+        self.observers.push(observer);
+    }
 }
 
 impl PokerEngine<'_> {
     pub fn new<'a>(
-        players: &'a Vec<&'a dyn GamePlayer<PokerState, PokerAction>>,
+        players: &'a Vec<&'a dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>>,
+        config: TableConfig,
     ) -> PokerEngine<'a> {
         let mut new_engine = PokerEngine {
             state: PokerState {
                 pot: 0,
+                pots: vec![],
                 player_stacks: HashMap::new(),
+                player_contributions: HashMap::new(),
                 community_cards: vec![],
                 player_cards: HashMap::new(),
                 last_action: HashMap::new(),
                 required_call: 0,
+                history: vec![],
+                button: 0,
+                positions: HashMap::new(),
             },
             deck: Deck::new(),
             players,
-            starting_player: 0,
+            blind_seats: None,
+            config,
+            illegal_actions: vec![],
+            hands_played: 0,
+            hand_records: vec![],
+            observers: vec![],
         };
 
-        // Players start with 200 in chips
         for i in 0..players.len() {
-            new_engine.state.player_stacks.insert(i, 200);
+            new_engine
+                .state
+                .player_stacks
+                .insert(i, new_engine.config.starting_stack);
         }
-        println!("{}", new_engine.players.len());
+        debug!(
+            players = players.len(),
+            starting_stack = new_engine.config.starting_stack;
+            "created poker engine"
+        );
         new_engine
     }
 
-    fn get_betting_action(&mut self) {
-        let mut last_bet = self.starting_player;
-        let mut current_player = self.starting_player;
-        let mut pot_commitment = HashMap::<usize, u64>::new();
-        let mut big_blind = false;
-        // Preflop
-        if self.state.community_cards.len() == 0 {
-            // Player 1 in for small blind
-            println!("{}", current_player);
-            *self
-                .state
-                .last_action
-                .entry(current_player)
-                .or_insert(PokerAction::Check) = PokerAction::Raise(1);
-            *pot_commitment.entry(current_player).or_insert(0) = 1;
-            current_player = (current_player + 1) % self.players.len();
+    /// Reseeds the deck so that every following hand is dealt reproducibly
+    pub fn seed(&mut self, seed: u64) {
+        self.deck = Deck::seeded(seed);
+    }
+
+    pub fn state(&self) -> &PokerState {
+        &self.state
+    }
+
+    /// Sets a player's chips between hands, such as for a rebuy. A player with no chips sits
+    /// out until they are given some.
+    pub fn set_stack(&mut self, player: usize, stack: u64) {
+        self.state.player_stacks.insert(player, stack);
+    }
 
-            println!("{}", current_player);
-            // Player 2 in for big blind
+    /// Every illegal action players have made, including those handled by the policy
+    pub fn illegal_actions(&self) -> &[PokerError] {
+        &self.illegal_actions
+    }
+
+    /// The latest hands played to the end, as many as `TableConfig::hand_record_limit` allows
+    pub fn hand_records(&self) -> &[HandRecord] {
+        &self.hand_records
+    }
+
+    /// Takes the hand records kept so far, leaving room for the next ones
+    pub fn take_hand_records(&mut self) -> Vec<HandRecord> {
+        std::mem::take(&mut self.hand_records)
+    }
+
+    /// Keeps the record of the hand just played for the observers and `hand_records`
+    fn record_hand(&mut self, stacks_before_hand: &HashMap<usize, u64>) {
+        self.hands_played += 1;
+        if self.config.hand_record_limit == 0 && self.observers.is_empty() {
+            return;
+        }
+        let started_at = match self.config.start_time {
+            Some(start_time) => start_time + (self.hands_played - 1) * 60,
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+        };
+        let starting_stacks = self
+            .state
+            .player_cards
+            .keys()
+            .map(|player| (*player, stacks_before_hand[player]))
+            .collect();
+        let record = HandRecord {
+            hand_number: self.hands_played,
+            started_at,
+            table_name: self.config.table_name.clone(),
+            num_seats: self.players.len(),
+            button: self.state.button,
+            small_blind: self.config.small_blind,
+            big_blind: self.config.big_blind,
+            betting_structure: self.config.betting_structure.clone(),
+            names: self.config.player_names.clone(),
+            starting_stacks,
+            history: self.state.history.clone(),
+        };
+        for observer in &self.observers {
+            observer.hand_recorded(&record);
+        }
+        if self.config.hand_record_limit > 0 {
+            if self.hand_records.len() == self.config.hand_record_limit {
+                self.hand_records.remove(0);
+            }
+            self.hand_records.push(record);
+        }
+    }
+
+    /// Deals the next card. A hand needs two cards per player plus eight for the board, so the
+    /// deck runs out with more than 22 players.
+    fn deal_card(&mut self) -> Result<Card, PokerError> {
+        self.deck.get_next().ok_or(PokerError::DeckExhausted)
+    }
+
+    /// Burns a card then deals the next street
+    fn deal_community_cards(&mut self, count: usize) -> Result<(), PokerError> {
+        self.deck.burn().ok_or(PokerError::DeckExhausted)?;
+        let cards = (0..count)
+            .map(|_| self.deal_card())
+            .collect::<Result<Vec<Card>, PokerError>>()?;
+        self.state.community_cards.extend(&cards);
+        for observer in &self.observers {
+            observer.street_dealt(self.state.street(), &cards, &self.state);
+        }
+        self.record(HandEvent::CommunityCards { cards });
+        Ok(())
+    }
+
+    /// Adds an event to the hand history under the current street
+    fn record(&mut self, event: HandEvent) {
+        let street = self.state.street();
+        self.state.history.push(HistoryEntry { street, event });
+    }
+
+    fn is_folded(&self, player: usize) -> bool {
+        self.state.last_action.get(&player) == Some(&PokerAction::Fold)
+    }
+
+    /// A player who is still in the hand but has no chips behind
+    fn is_all_in(&self, player: usize) -> bool {
+        !self.is_folded(player) && self.state.player_stacks[&player] == 0
+    }
+
+    /// Players still in the hand with chips left to bet
+    fn players_able_to_act(&self) -> HashSet<usize> {
+        (0..self.players.len())
+            .filter(|player| !self.is_folded(*player) && !self.is_all_in(*player))
+            .collect()
+    }
+
+    fn players_in_hand(&self) -> usize {
+        (0..self.players.len())
+            .filter(|player| !self.is_folded(*player))
+            .count()
+    }
+
+    fn folded_players(&self) -> HashSet<usize> {
+        (0..self.players.len())
+            .filter(|player| self.is_folded(*player))
+            .collect()
+    }
+
+    /// The next seat after `seat` whose player has chips to play a hand with
+    fn next_with_chips(&self, seat: usize) -> usize {
+        let mut next = (seat + 1) % self.players.len();
+        while self.state.player_stacks[&next] == 0 && next != seat {
+            next = (next + 1) % self.players.len();
+        }
+        next
+    }
+
+    /// Moves the button and blinds on for a new hand under the dead button rule.
+    ///
+    /// The big blind moves to the next player with chips, the small blind to the seat that had
+    /// the big blind and the button to the seat that had the small blind, so a player who busts
+    /// can leave a dead small blind or button behind. Heads-up the button posts the small blind.
+    fn move_button(&mut self, num_active: usize) {
+        let (button, seats) = match self.blind_seats {
+            Some(last) if num_active > 2 => (
+                last.small_blind,
+                BlindSeats {
+                    small_blind: last.big_blind,
+                    big_blind: self.next_with_chips(last.big_blind),
+                },
+            ),
+            Some(last) => {
+                let big_blind = self.next_with_chips(last.big_blind);
+                let button = self.next_with_chips(big_blind);
+                (
+                    button,
+                    BlindSeats {
+                        small_blind: button,
+                        big_blind,
+                    },
+                )
+            }
+            // The first player with chips posts the first small blind
+            None => {
+                let small_blind = self.next_with_chips(self.players.len() - 1);
+                let button = if num_active > 2 {
+                    (0..self.players.len())
+                        .rev()
+                        .find(|seat| self.state.player_stacks[seat] > 0)
+                        .unwrap()
+                } else {
+                    small_blind
+                };
+                (
+                    button,
+                    BlindSeats {
+                        small_blind,
+                        big_blind: self.next_with_chips(small_blind),
+                    },
+                )
+            }
+        };
+        self.state.button = button;
+        self.blind_seats = Some(seats);
+    }
+
+    /// The seats from the one after the button round to the button
+    fn seats_from_button(&self) -> impl Iterator<Item = usize> {
+        let num_seats = self.players.len();
+        let button = self.state.button;
+        (1..=num_seats).map(move |offset| (button + offset) % num_seats)
+    }
+
+    /// The next player after `player` who has not folded
+    fn next_in_hand(&self, player: usize) -> usize {
+        let mut next = (player + 1) % self.players.len();
+        while self.is_folded(next) && next != player {
+            next = (next + 1) % self.players.len();
+        }
+        next
+    }
+
+    /// Moves up to `amount` chips from the player's stack into the pot as dead money, returning
+    /// how much was actually posted. A player who cannot cover the amount is put all-in.
+    fn post_dead_chips(&mut self, player: usize, amount: u64) -> u64 {
+        let stack = self.state.player_stacks.get_mut(&player).unwrap();
+        let committed = amount.min(*stack);
+        *stack -= committed;
+        self.state.pot += committed;
+        *self.state.player_contributions.entry(player).or_insert(0) += committed;
+        committed
+    }
+
+    /// Moves up to `amount` chips from the player's stack into the pot towards their bet this
+    /// street, returning how much was actually committed.
+    fn commit_chips(
+        &mut self,
+        player: usize,
+        amount: u64,
+        street_commitment: &mut HashMap<usize, u64>,
+    ) -> u64 {
+        let committed = self.post_dead_chips(player, amount);
+        *street_commitment.entry(player).or_insert(0) += committed;
+        committed
+    }
+
+    fn post_ante(&mut self, player: usize, amount: u64) {
+        let amount = self.post_dead_chips(player, amount);
+        for observer in &self.observers {
+            observer.chips_posted(player, amount, None);
+        }
+        self.record(HandEvent::PostAnte { player, amount });
+    }
+
+    /// Posts a live blind, recorded as a raise of the blind over the current bet
+    fn post_blind(
+        &mut self,
+        player: usize,
+        blind: Blind,
+        amount: u64,
+        street_commitment: &mut HashMap<usize, u64>,
+    ) {
+        self.state.last_action.insert(
+            player,
+            PokerAction::Raise(amount.saturating_sub(self.state.required_call)),
+        );
+        let amount = self.commit_chips(player, amount, street_commitment);
+        for observer in &self.observers {
+            observer.chips_posted(player, amount, Some(blind));
+        }
+        self.record(HandEvent::PostBlind {
+            player,
+            blind,
+            amount,
+        });
+        self.state.required_call = self.state.required_call.max(amount);
+    }
+
+    /// Posts antes and blinds, returning the first player to act preflop
+    fn post_forced_bets(&mut self, street_commitment: &mut HashMap<usize, u64>) -> usize {
+        let seats = self
+            .blind_seats
+            .expect("the button is moved before posting blinds");
+        if self.config.ante > 0 {
+            let dealt: Vec<usize> = self
+                .seats_from_button()
+                .filter(|player| !self.is_folded(*player))
+                .collect();
+            for player in dealt {
+                self.post_ante(player, self.config.ante);
+            }
+        }
+
+        // A busted player leaves the small blind dead
+        let small_blind = seats.small_blind;
+        if !self.is_folded(small_blind) {
+            self.post_blind(
+                small_blind,
+                Blind::Small,
+                self.config.small_blind,
+                street_commitment,
+            );
+        }
+
+        let big_blind = seats.big_blind;
+        if self.config.big_blind_ante > 0 {
+            self.post_ante(big_blind, self.config.big_blind_ante);
+        }
+        self.post_blind(
+            big_blind,
+            Blind::Big,
+            self.config.big_blind,
+            street_commitment,
+        );
+        let mut last_blind = big_blind;
+
+        // A straddle needs a third player who isn't one of the blinds and can raise the big blind
+        let straddler = self.next_in_hand(big_blind);
+        if let Some(straddle) = self.config.straddle {
+            if straddler != small_blind
+                && straddle > self.config.big_blind
+                && self.state.player_stacks[&straddler] > self.config.big_blind
+            {
+                self.post_blind(straddler, Blind::Straddle, straddle, street_commitment);
+                last_blind = straddler;
+            }
+        }
+        self.next_in_hand(last_blind)
+    }
+
+    /// Returns the part of the largest bet this street that no other player matched
+    fn return_uncalled_bet(&mut self, street_commitment: &HashMap<usize, u64>) {
+        let mut commitments: Vec<(&usize, &u64)> = street_commitment.iter().collect();
+        commitments.sort_by(|a, b| b.1.cmp(a.1));
+        let (top_player, top_amount) = match commitments.first() {
+            Some((player, amount)) => (**player, **amount),
+            None => return,
+        };
+        let second_amount = commitments.get(1).map_or(0, |(_, amount)| **amount);
+        let uncalled = top_amount - second_amount;
+        if uncalled > 0 {
+            *self.state.player_stacks.get_mut(&top_player).unwrap() += uncalled;
             *self
                 .state
-                .last_action
-                .entry(current_player)
-                .or_insert(PokerAction::Check) = PokerAction::Raise(1);
-            *pot_commitment.entry(current_player).or_insert(0) = 2;
-            current_player = (current_player + 1) % self.players.len();
-            last_bet = current_player;
-            big_blind = true;
-            self.state.required_call = 2;
+                .player_contributions
+                .get_mut(&top_player)
+                .unwrap() -= uncalled;
+            self.state.pot -= uncalled;
+            self.record(HandEvent::UncalledBetReturned {
+                player: top_player,
+                amount: uncalled,
+            });
         }
+    }
+
+    /// The smallest and largest amounts the player may raise by
+    fn raise_bounds(
+        &self,
+        player: usize,
+        to_call: u64,
+        last_raise: u64,
+        bets: u32,
+    ) -> Option<(u64, u64)> {
+        self.config
+            .betting_structure
+            .raise_bounds(&BettingSituation {
+                pot: self.state.pot,
+                to_call,
+                stack: self.state.player_stacks[&player],
+                last_raise,
+                big_blind: self.config.big_blind,
+                big_bet_street: self.state.community_cards.len() > 3,
+                bets,
+            })
+    }
+
+    /// Asks a player for an action, applying the illegal action policy until a legal one is found
+    fn get_legal_action(
+        &mut self,
+        player: usize,
+        observation: &PlayerObservation,
+        legal_actions: &LegalActions,
+    ) -> Result<PokerAction, PokerError> {
+        let mut attempts = 0;
+        loop {
+            let action = self.players[player].get_action(observation, legal_actions);
+            let reason = match legal_actions.validate(&action) {
+                Ok(()) => return Ok(action),
+                Err(reason) => reason,
+            };
+            warn!(player, action:?, reason:?; "rejected illegal action");
+            let error = PokerError::IllegalAction {
+                player,
+                action,
+                reason,
+            };
+            self.illegal_actions.push(error.clone());
+            attempts += 1;
+            match self.config.illegal_action_policy {
+                IllegalActionPolicy::Reask { max_attempts } if attempts < max_attempts => (),
+                IllegalActionPolicy::Abort => return Err(error),
+                _ if legal_actions.can_fold => return Ok(PokerAction::Fold),
+                _ => return Ok(PokerAction::Check),
+            }
+        }
+    }
 
-        let mut available_actions: Vec<PokerAction> = vec![];
-        while current_player != last_bet || big_blind {
-            if current_player == last_bet {
-                big_blind = false;
+    /// Plays a street's betting. Preflop `forced_bets` holds what the antes and blinds put in
+    /// this street and the first player to act after them.
+    fn get_betting_action(
+        &mut self,
+        forced_bets: Option<(HashMap<usize, u64>, usize)>,
+    ) -> Result<(), PokerError> {
+        let mut round = BettingRound {
+            commitment: HashMap::new(),
+            last_raise: 0,
+            bets: 0,
+            to_act: HashSet::new(),
+            raising_closed: HashSet::new(),
+        };
+        let mut current_player = match forced_bets {
+            Some((commitment, first_to_act)) => {
+                round.commitment = commitment;
+                // The blinds open the betting, so a raise must be at least the size of the largest
+                round.last_raise = self.state.required_call;
+                round.bets = blind_bets(self.state.required_call, self.config.big_blind);
+                first_to_act
+            }
+            None => {
+                // Action starts with the first player after the button still in the hand
+                self.state.required_call = 0;
+                self.next_in_hand(self.state.button)
             }
-            if self.state.last_action[&current_player] == PokerAction::Fold {
+        };
+
+        // Everyone who can still bet acts at least once, which gives the big blind their option
+        round.to_act = self.players_able_to_act();
+        while !round.to_act.is_empty() && self.players_in_hand() > 1 {
+            if !round.to_act.remove(&current_player) {
                 current_player = (current_player + 1) % self.players.len();
                 continue;
             }
 
-            if self.state.required_call > 0 {
-                available_actions =
-                    vec![PokerAction::Fold, PokerAction::Call, PokerAction::Raise(0)];
-            } else {
-                available_actions = vec![PokerAction::Check, PokerAction::Raise(0)];
+            let to_call =
+                self.state.required_call - round.commitment.get(&current_player).unwrap_or(&0);
+            // With everyone else all-in there is nobody left to bet against
+            if to_call == 0 && self.players_able_to_act().len() == 1 {
+                continue;
             }
-            let mut player_view = self.state.clone();
-            player_view.player_cards.clear();
-            player_view
-                .player_cards
-                .insert(current_player, self.state.player_cards[&current_player]);
-
-            let action = self.players[current_player].get_action(&player_view, &available_actions);
-            match action {
+            let raise_range = if round.raising_closed.contains(&current_player) {
+                None
+            } else {
+                self.raise_bounds(current_player, to_call, round.last_raise, round.bets)
+            };
+            let legal_actions = LegalActions::new(
+                to_call,
+                self.state.player_stacks[&current_player],
+                raise_range,
+            );
+            let observation = self.state.observation(current_player);
+            let action = self.get_legal_action(current_player, &observation, &legal_actions)?;
+            let amount = match action {
                 PokerAction::Call => {
-                    *pot_commitment.entry(current_player).or_insert(0) = self.state.required_call;
+                    self.commit_chips(current_player, to_call, &mut round.commitment)
                 }
                 PokerAction::Raise(value) => {
-                    self.state.required_call += value;
-                    *pot_commitment.entry(current_player).or_insert(0) = self.state.required_call;
-                    last_bet = current_player;
+                    let committed =
+                        self.commit_chips(current_player, to_call + value, &mut round.commitment);
+                    // A short all-in only raises by as much as the player could cover
+                    let total = round.commitment[&current_player];
+                    if total > self.state.required_call {
+                        let raise = total - self.state.required_call;
+                        self.state.required_call = total;
+                        if raise >= round.last_raise {
+                            // A full raise reopens the betting for everyone else
+                            round.last_raise = raise;
+                            round.bets += 1;
+                            round.to_act = self.players_able_to_act();
+                            round.raising_closed.clear();
+                        } else {
+                            // A short all-in doesn't, so players who already acted may only
+                            // call the extra chips or fold
+                            for player in self.players_able_to_act() {
+                                if round.to_act.insert(player) {
+                                    round.raising_closed.insert(player);
+                                }
+                            }
+                        }
+                        round.to_act.remove(&current_player);
+                    }
+                    committed
                 }
-                _ => (),
+                _ => 0,
+            };
+            self.record(HandEvent::Action {
+                player: current_player,
+                action: action.clone(),
+                amount,
+                all_in: amount > 0 && self.state.player_stacks[&current_player] == 0,
+            });
+            trace!(player = current_player, action:?, amount; "player acted");
+            for observer in &self.observers {
+                observer.action_taken(current_player, &action, amount, &self.state);
             }
-            *self
-                .state
-                .last_action
-                .entry(current_player)
-                .or_insert(PokerAction::Check) = action;
+            self.state.last_action.insert(current_player, action);
             current_player = (current_player + 1) % self.players.len();
         }
 
-        for (player, value) in pot_commitment {
-            self.state.pot += value;
-            *self.state.player_stacks.get_mut(&player).unwrap() -= value;
+        self.return_uncalled_bet(&round.commitment);
+        self.state.pots = build_pots(&self.state.player_contributions, &self.folded_players());
+        Ok(())
+    }
+
+    /// Moves the button on and plays a single hand. Nothing is played once fewer than two
+    /// players have chips left.
+    ///
+    /// If the hand is aborted over an illegal action every player gets back what they bet in it.
+    pub fn play_hand(&mut self) -> Result<(), PokerError> {
+        let stacks_before_hand = self.state.player_stacks.clone();
+        let result = self.play_streets();
+        if let Err(error) = &result {
+            warn!(error:% = error; "hand aborted, restoring stacks");
+            self.state.player_stacks = stacks_before_hand;
+        } else if !self.state.player_cards.is_empty() {
+            self.record_hand(&stacks_before_hand);
+            for observer in &self.observers {
+                observer.hand_finished(&self.state);
+            }
+            let mut busted: Vec<usize> = self
+                .state
+                .player_cards
+                .keys()
+                .filter(|player| self.state.player_stacks[player] == 0)
+                .copied()
+                .collect();
+            busted.sort();
+            for player in busted {
+                for observer in &self.observers {
+                    observer.player_busted(player);
+                }
+            }
         }
+
+        self.state.pot = 0;
+        self.state.pots.clear();
+        self.state.player_contributions.clear();
+        self.state.required_call = 0;
+        self.state.last_action.clear();
+        self.state.community_cards = vec![];
+        self.state.player_cards.clear();
+        result
     }
-}
 
-impl GameEngine<PokerState, PokerAction> for PokerEngine<'_> {
-    fn run(&mut self) {
-        // Start with 5 rounds.
-        for _round_num in 0..5 {
-            self.state.community_cards = vec![];
-            // Shuffle Deck
-            self.deck.shuffle();
-            // Deal cards
-            for player_id in 0..self.players.len() {
-                self.state.player_cards.insert(
-                    (self.starting_player + player_id) % self.players.len(),
-                    (self.deck.get_next(), self.deck.get_next()),
-                );
+    fn play_streets(&mut self) -> Result<(), PokerError> {
+        self.state.community_cards = vec![];
+        self.state.history.clear();
+        let num_active = (0..self.players.len())
+            .filter(|player| self.state.player_stacks[player] > 0)
+            .count();
+        if num_active < 2 {
+            return Ok(());
+        }
+        self.move_button(num_active);
+        let dealt: Vec<usize> = self
+            .seats_from_button()
+            .filter(|player| self.state.player_stacks[player] > 0)
+            .collect();
+        let small_blind = self.blind_seats.unwrap().small_blind;
+        self.state.positions = assign_positions(&dealt, self.state.player_stacks[&small_blind] > 0);
+        debug!(
+            hand = self.hands_played + 1,
+            button = self.state.button,
+            players = dealt.len();
+            "starting hand"
+        );
+        for observer in &self.observers {
+            observer.hand_started(&self.state);
+        }
+        // Players with no chips left sit the hand out
+        for player in 0..self.players.len() {
+            if self.state.player_stacks[&player] == 0 {
+                self.state.last_action.insert(player, PokerAction::Fold);
             }
+        }
+        // Antes and blinds go in before any cards are dealt
+        let mut forced_commitment = HashMap::new();
+        let first_to_act = self.post_forced_bets(&mut forced_commitment);
+        let mut forced_bets = Some((forced_commitment, first_to_act));
 
-            // Get preflop action
-            self.get_betting_action();
-            for _ in 0..3 {
-                self.state.community_cards.push(self.deck.get_next());
+        // Shuffle Deck
+        self.deck.shuffle();
+        // Deal cards from the small blind
+        let seats: Vec<usize> = self.seats_from_button().collect();
+        for player in seats {
+            if self.is_folded(player) {
+                continue;
             }
-            // Get flop action
-            self.get_betting_action();
-            self.state.community_cards.push(self.deck.get_next());
-            // Get turn action
-            self.get_betting_action();
-            self.state.community_cards.push(self.deck.get_next());
-            println!("{}", self.state);
-            // Get river action
-            self.get_betting_action();
-            // Showdown
-
-            let mut max_value = 0;
-            let mut max_player: Vec<usize> = vec![];
-            for i in 0..self.players.len() {
-                if self.state.last_action.get(&i).unwrap() == &PokerAction::Fold {
-                    continue;
-                }
-                let (player_card1, player_card2) = &self.state.player_cards[&i];
-                let mut cards = vec![*player_card1, *player_card2];
-                cards.append(&mut self.state.community_cards.clone());
-                let value = get_hand_value(&cards);
-                println!("Player {} of {}: Score {}", i, self.players.len(), value);
-                if value > max_value {
-                    max_value = value;
-                    max_player = vec![i];
-                } else if value == max_value {
-                    max_player.push(i);
-                }
+            let hole_cards = (self.deal_card()?, self.deal_card()?);
+            self.state.player_cards.insert(player, hole_cards);
+            for observer in &self.observers {
+                observer.cards_dealt(player, hole_cards);
+            }
+            self.record(HandEvent::HoleCards {
+                player,
+                cards: hole_cards,
+            });
+        }
+
+        // Preflop action, then the flop, turn and river each followed by their action
+        for cards_to_deal in [0, 3, 1, 1] {
+            if cards_to_deal > 0 {
+                self.deal_community_cards(cards_to_deal)?;
             }
-            for player in &max_player {
-                *self.state.player_stacks.get_mut(&player).unwrap() +=
-                    self.state.pot / (max_player.len() as u64);
+            self.get_betting_action(forced_bets.take())?;
+            // Everyone else folded, so the hand ends without a showdown
+            if self.players_in_hand() == 1 {
+                self.award_uncontested_pot();
+                return Ok(());
             }
-            println!("Player {:?} wins {}!\n", max_player, self.state.pot);
+        }
+        // Showdown
+        self.showdown();
+        Ok(())
+    }
+
+    /// Gives the whole pot to the only player who hasn't folded
+    fn award_uncontested_pot(&mut self) {
+        let winner = (0..self.players.len())
+            .find(|player| !self.is_folded(*player))
+            .unwrap();
+        let amount = self.state.pot;
+        *self.state.player_stacks.get_mut(&winner).unwrap() += amount;
+        debug!(player = winner, pot = 0, amount; "awarded uncontested pot");
+        for observer in &self.observers {
+            observer.pot_awarded(winner, 0, amount);
+        }
+        self.record(HandEvent::WinsPot {
+            player: winner,
+            pot: 0,
+            amount,
+        });
+    }
+
+    /// Pays each pot to the best hand among the players eligible for it
+    fn showdown(&mut self) {
+        let mut hand_values = HashMap::<usize, HandRank>::new();
+        for i in 0..self.players.len() {
+            if self.is_folded(i) {
+                continue;
+            }
+            let (player_card1, player_card2) = &self.state.player_cards[&i];
+            let mut cards = vec![*player_card1, *player_card2];
+            cards.append(&mut self.state.community_cards.clone());
+            hand_values.insert(i, get_hand_value(&cards));
+        }
+        for observer in &self.observers {
+            observer.showdown(&hand_values, &self.state);
+        }
 
-            self.starting_player = (self.starting_player + 1) % self.players.len();
-            self.state.pot = 0;
-            self.state.required_call = 0;
-            self.state.last_action.clear();
-            self.state.community_cards = vec![];
-            self.state.player_cards.clear();
+        let pots = build_pots(&self.state.player_contributions, &self.folded_players());
+        for (pot_idx, pot) in pots.iter().enumerate() {
+            let max_value = pot
+                .eligible_players
+                .iter()
+                .map(|player| &hand_values[player])
+                .max()
+                .unwrap();
+            // Winners are ordered from the button's left so any odd chips go to them first
+            let mut max_player: Vec<usize> = pot
+                .eligible_players
+                .iter()
+                .filter(|player| &hand_values[player] == max_value)
+                .copied()
+                .collect();
+            max_player.sort_by_key(|player| {
+                (player + self.players.len() - self.state.button - 1) % self.players.len()
+            });
+            let share = pot.amount / (max_player.len() as u64);
+            let odd_chips = pot.amount % (max_player.len() as u64);
+            for (idx, player) in max_player.iter().enumerate() {
+                let extra = if (idx as u64) < odd_chips { 1 } else { 0 };
+                *self.state.player_stacks.get_mut(player).unwrap() += share + extra;
+                debug!(player = *player, pot = pot_idx, amount = share + extra; "awarded pot");
+                for observer in &self.observers {
+                    observer.pot_awarded(*player, pot_idx, share + extra);
+                }
+                self.record(HandEvent::WinsPot {
+                    player: *player,
+                    pot: pot_idx,
+                    amount: share + extra,
+                });
+            }
         }
+        self.state.pots = pots;
+    }
+}
+
+impl GameEngine<PokerState, PokerAction> for PokerEngine<'_> {
+    type Error = PokerError;
+
+    fn run(&mut self) -> Result<(), PokerError> {
+        // Start with 5 rounds.
+        for _round_num in 0..5 {
+            self.play_hand()?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the blinds, the showdown and the winners of each hand to stdout
+pub struct ConsoleObserver {}
+
+impl PokerObserver for ConsoleObserver {
+    fn chips_posted(&self, player: usize, amount: u64, blind: Option<Blind>) {
+        if let Some(blind) = blind {
+            println!(
+                "Player {} posts the {:?} blind of {}",
+                player, blind, amount
+            );
+        }
+    }
+
+    fn showdown(&self, hands: &HashMap<usize, HandRank>, state: &PokerState) {
+        println!("{}", state);
+        let mut players: Vec<&usize> = hands.keys().collect();
+        players.sort();
+        for player in players {
+            let value = &hands[player];
+            println!(
+                "Player {} of {}: {:?} {:?}",
+                player,
+                state.player_stacks.len(),
+                value.category,
+                value.kickers
+            );
+        }
+    }
+
+    fn pot_awarded(&self, player: usize, _pot: usize, amount: u64) {
+        println!("Player {} wins {}!", player, amount);
+    }
+
+    fn player_busted(&self, player: usize) {
+        println!("Player {} is out of chips", player);
     }
 }
 
 pub struct PassivePokerPlayer {}
 
-impl GamePlayer<PokerState, PokerAction> for PassivePokerPlayer {
-    fn get_action(&self, _: &PokerState, actions: &Vec<PokerAction>) -> PokerAction {
-        if actions.contains(&PokerAction::Check) {
+impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for PassivePokerPlayer {
+    fn get_action(&self, _: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+        if actions.can_check {
             PokerAction::Check
         } else {
             PokerAction::Call
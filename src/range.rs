@@ -0,0 +1,289 @@
+use crate::card::{parse_cards, Card, CardSet, ParseCardError, Rank, Suit};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Two specific hole cards and how often they are in the range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Combo {
+    pub cards: (Card, Card),
+    /// The fraction of the time the combo is played, from 0 to 1
+    pub weight: f64,
+}
+
+/// A weighted set of hole card combos, such as a preflop opening range.
+///
+/// Ranges are parsed from standard notation like `22+, A2s+, KTo+, QJs, 72o, AKs:0.5`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Range {
+    /// Weights keyed by the `CardSet` mask of each combo's two cards
+    weights: BTreeMap<u64, f64>,
+}
+
+impl Range {
+    pub fn new() -> Self {
+        Range {
+            weights: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the weight of a combo, replacing any earlier weight. A weight of 0 removes it.
+    pub fn insert(&mut self, cards: (Card, Card), weight: f64) {
+        let mask = CardSet::from_iter([cards.0, cards.1]).mask();
+        if weight > 0.0 {
+            self.weights.insert(mask, weight);
+        } else {
+            self.weights.remove(&mask);
+        }
+    }
+
+    /// The weight of a combo, 0 when it isn't in the range
+    pub fn weight(&self, cards: (Card, Card)) -> f64 {
+        let mask = CardSet::from_iter([cards.0, cards.1]).mask();
+        self.weights.get(&mask).copied().unwrap_or(0.0)
+    }
+
+    pub fn contains(&self, cards: (Card, Card)) -> bool {
+        self.weight(cards) > 0.0
+    }
+
+    /// The combos in the range with the higher card first, ordered by their cards
+    pub fn combos(&self) -> Vec<Combo> {
+        self.weights
+            .iter()
+            .map(|(mask, weight)| {
+                let mut cards = CardSet::from_mask(*mask).iter();
+                let first = cards.next().unwrap();
+                let second = cards.next().unwrap();
+                Combo {
+                    cards: if second.rank > first.rank {
+                        (second, first)
+                    } else {
+                        (first, second)
+                    },
+                    weight: *weight,
+                }
+            })
+            .collect()
+    }
+
+    /// The number of combos with any weight
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// The weighted number of combos
+    pub fn total_weight(&self) -> f64 {
+        self.weights.values().sum()
+    }
+
+    /// The range without the combos that use any of `cards`, such as the board or known hands
+    pub fn without(&self, cards: &CardSet) -> Range {
+        Range {
+            weights: self
+                .weights
+                .iter()
+                .filter(|(mask, _)| *mask & cards.mask() == 0)
+                .map(|(mask, weight)| (*mask, *weight))
+                .collect(),
+        }
+    }
+}
+
+/// Why range notation couldn't be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseRangeError {
+    /// An entry that isn't a hand, a `+` or `-` span of hands or two specific cards
+    InvalidEntry(String),
+    /// A weight after `:` that isn't a number from 0 to 1
+    InvalidWeight(String),
+    InvalidCards(ParseCardError),
+}
+
+impl Display for ParseRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseRangeError::InvalidEntry(entry) => write!(f, "invalid range entry \"{}\"", entry),
+            ParseRangeError::InvalidWeight(weight) => write!(f, "invalid weight \"{}\"", weight),
+            ParseRangeError::InvalidCards(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for ParseRangeError {}
+
+impl From<ParseCardError> for ParseRangeError {
+    fn from(error: ParseCardError) -> Self {
+        ParseRangeError::InvalidCards(error)
+    }
+}
+
+/// A hand written without suits, such as `QQ`, `AKs`, `T9o` or `KJ`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HandClass {
+    high: Rank,
+    low: Rank,
+    /// `None` for pairs and for hands written without `s` or `o`, which mean both
+    suited: Option<bool>,
+}
+
+impl HandClass {
+    fn parse(s: &str) -> Result<Self, ParseRangeError> {
+        let invalid = || ParseRangeError::InvalidEntry(s.to_string());
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() < 2 || chars.len() > 3 {
+            return Err(invalid());
+        }
+        let first = Rank::from_char(chars[0])?;
+        let second = Rank::from_char(chars[1])?;
+        let suited = match chars.get(2).map(|c| c.to_ascii_lowercase()) {
+            None => None,
+            Some('s') => Some(true),
+            Some('o') => Some(false),
+            Some(_) => return Err(invalid()),
+        };
+        if first == second && suited.is_some() {
+            return Err(invalid());
+        }
+        Ok(HandClass {
+            high: first.max(second),
+            low: first.min(second),
+            suited,
+        })
+    }
+
+    fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    fn with_ranks(&self, high: Rank, low: Rank) -> HandClass {
+        HandClass {
+            high,
+            low,
+            suited: self.suited,
+        }
+    }
+
+    fn combos(&self) -> Vec<(Card, Card)> {
+        let mut combos = vec![];
+        for first in Suit::iterator() {
+            for second in Suit::iterator() {
+                let suited = first == second;
+                let wanted = match self.suited {
+                    _ if self.is_pair() => (*first as usize) < (*second as usize),
+                    Some(want_suited) => suited == want_suited,
+                    None => true,
+                };
+                if wanted {
+                    combos.push((
+                        Card {
+                            rank: self.high,
+                            suit: *first,
+                        },
+                        Card {
+                            rank: self.low,
+                            suit: *second,
+                        },
+                    ));
+                }
+            }
+        }
+        combos
+    }
+}
+
+/// The ranks from `low` to `high` inclusive
+fn ranks_between(low: Rank, high: Rank) -> impl Iterator<Item = Rank> {
+    Rank::iterator()
+        .copied()
+        .filter(move |rank| *rank >= low && *rank <= high)
+}
+
+/// Expands one entry without its weight into its hand classes or specific combos
+fn parse_entry(entry: &str) -> Result<Vec<(Card, Card)>, ParseRangeError> {
+    let invalid = || ParseRangeError::InvalidEntry(entry.to_string());
+    let chars: Vec<char> = entry.chars().collect();
+    // Specific cards such as `AsKs`
+    if chars.len() == 4 && Suit::from_char(chars[1]).is_ok() {
+        let cards = parse_cards(entry)?;
+        if cards[0] == cards[1] {
+            return Err(invalid());
+        }
+        return Ok(vec![(cards[0], cards[1])]);
+    }
+
+    let classes: Vec<HandClass> = if let Some(start) = entry.strip_suffix('+') {
+        // `22+` adds every higher pair, `A2s+` every higher kicker below the ace
+        let class = HandClass::parse(start)?;
+        if class.is_pair() {
+            ranks_between(class.low, Rank::Ace)
+                .map(|rank| class.with_ranks(rank, rank))
+                .collect()
+        } else {
+            ranks_between(class.low, Rank::from(class.high as i64 - 1))
+                .map(|rank| class.with_ranks(class.high, rank))
+                .collect()
+        }
+    } else if let Some((from, to)) = entry.split_once('-') {
+        // `22-55` spans pairs and `A2s-A5s` spans kickers of the same high card
+        let from = HandClass::parse(from)?;
+        let to = HandClass::parse(to)?;
+        if from.is_pair() && to.is_pair() {
+            ranks_between(from.low.min(to.low), from.low.max(to.low))
+                .map(|rank| from.with_ranks(rank, rank))
+                .collect()
+        } else if from.high == to.high && from.suited == to.suited && !from.is_pair() {
+            ranks_between(from.low.min(to.low), from.low.max(to.low))
+                .map(|rank| from.with_ranks(from.high, rank))
+                .collect()
+        } else {
+            return Err(invalid());
+        }
+    } else {
+        vec![HandClass::parse(entry)?]
+    };
+    Ok(classes.iter().flat_map(HandClass::combos).collect())
+}
+
+/// Parses range notation such as `22+, A2s+, KTo+, QJs, 72o, AKs:0.5, AhKh`.
+///
+/// Entries are separated by commas and may end with a `:weight` from 0 to 1. An entry sets the
+/// weight of its combos, so a later entry overrides an earlier one.
+pub fn parse_range(s: &str) -> Result<Range, ParseRangeError> {
+    let mut range = Range::new();
+    for entry in s.split(',') {
+        let entry: String = entry.chars().filter(|c| !c.is_whitespace()).collect();
+        if entry.is_empty() {
+            continue;
+        }
+        let (hands, weight) = match entry.split_once(':') {
+            Some((hands, weight)) => {
+                let parsed: f64 = weight
+                    .parse()
+                    .map_err(|_| ParseRangeError::InvalidWeight(weight.to_string()))?;
+                if !(0.0..=1.0).contains(&parsed) {
+                    return Err(ParseRangeError::InvalidWeight(weight.to_string()));
+                }
+                (hands, parsed)
+            }
+            None => (entry.as_str(), 1.0),
+        };
+        for combo in parse_entry(hands)? {
+            range.insert(combo, weight);
+        }
+    }
+    Ok(range)
+}
+
+impl FromStr for Range {
+    type Err = ParseRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_range(s)
+    }
+}
@@ -0,0 +1,225 @@
+use poker::card::{Card, CardSet, Rank, Suit};
+use poker::new_card;
+
+#[cfg(test)]
+mod card_set_tests {
+    use super::*;
+
+    #[test]
+    fn card_index_round_trips() {
+        for index in 0..52 {
+            assert_eq!(Card::from_index(index).index(), index);
+        }
+    }
+
+    #[test]
+    fn insert_remove_and_contains() {
+        let mut set = CardSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(new_card!(Ace, Spade)));
+        assert!(!set.insert(new_card!(Ace, Spade)));
+        assert!(set.insert(new_card!(Two, Heart)));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&new_card!(Ace, Spade)));
+        assert!(!set.contains(&new_card!(Ace, Heart)));
+        assert!(set.remove(&new_card!(Ace, Spade)));
+        assert!(!set.remove(&new_card!(Ace, Spade)));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: CardSet = vec![
+            new_card!(Ace, Spade),
+            new_card!(King, Spade),
+            new_card!(Queen, Heart),
+        ]
+        .into_iter()
+        .collect();
+        let b: CardSet = vec![new_card!(King, Spade), new_card!(Two, Club)]
+            .into_iter()
+            .collect();
+        assert_eq!((a | b).len(), 4);
+        assert_eq!(a & b, CardSet::from(&vec![new_card!(King, Spade)]));
+        assert_eq!((a - b).len(), 2);
+        assert!(!(a - b).contains(&new_card!(King, Spade)));
+        assert!(!a.is_disjoint(&b));
+        assert!((a - b).is_disjoint(&b));
+    }
+
+    #[test]
+    fn suit_and_rank_masks() {
+        assert_eq!(CardSet::of_suit(Suit::Club).len(), 13);
+        assert_eq!(CardSet::of_rank(Rank::Ten).len(), 4);
+        assert_eq!(
+            CardSet::of_suit(Suit::Diamond) & CardSet::of_rank(Rank::Ten),
+            CardSet::from(&vec![new_card!(Ten, Diamond)])
+        );
+        let set = CardSet::from(&vec![
+            new_card!(Two, Heart),
+            new_card!(Ace, Heart),
+            new_card!(Five, Club),
+        ]);
+        assert_eq!(set.suit_ranks(Suit::Heart), 1 << 12 | 1);
+        assert_eq!(set.suit_ranks(Suit::Spade), 0);
+        assert_eq!(set.ranks(), 1 << 12 | 1 << 3 | 1);
+    }
+
+    #[test]
+    fn full_set_iterates_every_card_once() {
+        let cards: Vec<Card> = CardSet::full().into();
+        assert_eq!(cards.len(), 52);
+        assert_eq!(CardSet::from(&cards), CardSet::full());
+    }
+}
+
+#[cfg(test)]
+mod notation_tests {
+    use super::*;
+    use poker::card::{format_cards, parse_cards, ParseCardError};
+
+    #[test]
+    fn parse_single_cards() {
+        assert_eq!("As".parse::<Card>(), Ok(new_card!(Ace, Spade)));
+        assert_eq!("Td".parse::<Card>(), Ok(new_card!(Ten, Diamond)));
+        assert_eq!("7c".parse::<Card>(), Ok(new_card!(Seven, Club)));
+        assert_eq!("2H".parse::<Card>(), Ok(new_card!(Two, Heart)));
+        assert_eq!("K".parse::<Rank>(), Ok(Rank::King));
+        assert_eq!("h".parse::<Suit>(), Ok(Suit::Heart));
+    }
+
+    #[test]
+    fn parse_rejects_bad_notation() {
+        assert_eq!("Xs".parse::<Card>(), Err(ParseCardError::InvalidRank('X')));
+        assert_eq!("Ax".parse::<Card>(), Err(ParseCardError::InvalidSuit('x')));
+        assert_eq!(
+            "10h".parse::<Card>(),
+            Err(ParseCardError::InvalidLength("10h".to_string()))
+        );
+        assert!("".parse::<Rank>().is_err());
+    }
+
+    #[test]
+    fn notation_round_trips_for_every_card() {
+        for card in CardSet::full() {
+            assert_eq!(card.notation().parse::<Card>(), Ok(card));
+        }
+    }
+
+    #[test]
+    fn parse_hands_and_boards() {
+        assert_eq!(
+            parse_cards("AsKd"),
+            Ok(vec![new_card!(Ace, Spade), new_card!(King, Diamond)])
+        );
+        assert_eq!(
+            parse_cards("Qh Jh 2c"),
+            Ok(vec![
+                new_card!(Queen, Heart),
+                new_card!(Jack, Heart),
+                new_card!(Two, Club)
+            ])
+        );
+        assert_eq!(parse_cards(""), Ok(vec![]));
+        assert!(parse_cards("QhJ").is_err());
+    }
+
+    #[test]
+    fn format_board() {
+        let board = parse_cards("QhJh2c").unwrap();
+        assert_eq!(format_cards(&board), "Qh Jh 2c");
+    }
+}
+
+#[cfg(test)]
+mod deck_tests {
+    use super::*;
+    use poker::card::Deck;
+
+    fn deal(deck: &mut Deck, n: usize) -> Vec<Card> {
+        (0..n).map(|_| deck.get_next().unwrap()).collect()
+    }
+
+    #[test]
+    fn same_seed_deals_same_cards() {
+        let mut deck1 = Deck::seeded(42);
+        let mut deck2 = Deck::seeded(42);
+        for _ in 0..3 {
+            deck1.shuffle();
+            deck2.shuffle();
+            assert_eq!(deal(&mut deck1, 52), deal(&mut deck2, 52));
+        }
+    }
+
+    #[test]
+    fn different_seeds_deal_different_cards() {
+        let mut deck1 = Deck::seeded(1);
+        let mut deck2 = Deck::seeded(2);
+        deck1.shuffle();
+        deck2.shuffle();
+        assert_ne!(deal(&mut deck1, 52), deal(&mut deck2, 52));
+    }
+}
+
+#[cfg(test)]
+mod deck_dealing_tests {
+    use super::*;
+    use poker::card::Deck;
+
+    #[test]
+    fn exhausted_deck_deals_nothing() {
+        let mut deck = Deck::seeded(3);
+        deck.shuffle();
+        let mut dealt = CardSet::new();
+        while let Some(card) = deck.get_next() {
+            assert!(dealt.insert(card));
+        }
+        assert_eq!(dealt, CardSet::full());
+        assert_eq!(deck.remaining(), 0);
+        assert_eq!(deck.burn(), None);
+    }
+
+    #[test]
+    fn removed_cards_are_never_dealt() {
+        let mut deck = Deck::seeded(5);
+        assert!(deck.remove(&new_card!(Ace, Spade)));
+        assert!(!deck.remove(&new_card!(Ace, Spade)));
+        let dead = CardSet::from(&vec![new_card!(King, Heart), new_card!(Two, Club)]);
+        deck.remove_set(&dead);
+        assert_eq!(deck.remaining(), 49);
+        for _ in 0..10 {
+            deck.shuffle();
+            while let Some(card) = deck.get_next() {
+                assert!(card != new_card!(Ace, Spade));
+                assert!(!dead.contains(&card));
+            }
+        }
+    }
+
+    #[test]
+    fn dealt_cards_cannot_be_removed() {
+        let mut deck = Deck::seeded(5);
+        deck.shuffle();
+        let card = deck.get_next().unwrap();
+        assert!(!deck.remove(&card));
+        assert_eq!(deck.remaining(), 51);
+    }
+
+    #[test]
+    fn burn_uses_up_a_card() {
+        let mut deck = Deck::seeded(9);
+        deck.shuffle();
+        let burned = deck.burn().unwrap();
+        assert_eq!(deck.remaining(), 51);
+        assert!(deck.get_next() != Some(burned));
+    }
+
+    #[test]
+    fn reset_restores_removed_cards() {
+        let mut deck = Deck::seeded(5);
+        deck.remove(&new_card!(Ace, Spade));
+        deck.get_next();
+        deck.reset();
+        assert_eq!(deck.remaining(), 52);
+    }
+}
@@ -0,0 +1,9 @@
+//! Helpers shared by the integration tests
+
+use poker::card::{parse_cards, Card};
+
+/// Two hole cards written together in card notation, such as `AhKh`
+pub fn hand(notation: &str) -> (Card, Card) {
+    let cards = parse_cards(notation).unwrap();
+    (cards[0], cards[1])
+}
@@ -0,0 +1,281 @@
+mod common;
+
+use common::hand;
+use poker::card::{parse_cards, Deck};
+use poker::equity::*;
+
+fn query(hands: &[&str], board: &str) -> EquityQuery {
+    EquityQuery::new(
+        hands.iter().map(|notation| hand(notation)).collect(),
+        parse_cards(board).unwrap(),
+    )
+}
+
+#[cfg(test)]
+mod monte_carlo_tests {
+    use super::*;
+
+    #[test]
+    fn ace_king_suited_against_queens_preflop() {
+        let result =
+            monte_carlo_equity(&query(&["AsKs", "QhQd"], ""), 20000, &mut Deck::seeded(1)).unwrap();
+        assert_eq!(result.runouts, 20000);
+        let ace_king = result.players[0];
+        let queens = result.players[1];
+        assert!((ace_king.equity - 0.46).abs() < 0.02, "{:?}", ace_king);
+        assert!((queens.equity - 0.54).abs() < 0.02, "{:?}", queens);
+        assert!((ace_king.equity + queens.equity - 1.0).abs() < 1e-9);
+        assert!(ace_king.std_error > 0.0 && ace_king.std_error < 0.005);
+    }
+
+    #[test]
+    fn equity_counts_ties_as_split_pots() {
+        let result = monte_carlo_equity(
+            &query(&["AsKd", "AhKc"], "2c 7d 9h"),
+            2000,
+            &mut Deck::seeded(3),
+        )
+        .unwrap();
+        for player in &result.players {
+            assert!(player.tie > 0.9);
+            assert!(player.win + player.tie <= 1.0);
+            assert!((player.equity - 0.5).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn complete_board_has_a_single_outcome() {
+        let result = monte_carlo_equity(
+            &query(&["AsKs", "QhQd", "7c7d"], "Ah Qc 7h 2s 3d"),
+            50,
+            &mut Deck::seeded(2),
+        )
+        .unwrap();
+        let equities: Vec<f64> = result.players.iter().map(|p| p.equity).collect();
+        assert_eq!(equities, vec![0.0, 1.0, 0.0]);
+        assert_eq!(result.players[1].win, 1.0);
+        assert_eq!(result.players[1].std_error, 0.0);
+    }
+
+    #[test]
+    fn dead_cards_are_not_dealt() {
+        // Every remaining queen is dead, so QQ can never improve to a set on a blank flop
+        let spot = query(&["AsKs", "QhQd"], "2c 7d 9h").with_dead(parse_cards("Qs Qc").unwrap());
+        let result = monte_carlo_equity(&spot, 2000, &mut Deck::seeded(5)).unwrap();
+        let ace_king = result.players[0];
+        let queens = result.players[1];
+        assert!(
+            ace_king.equity > 0.2 && ace_king.equity < 0.35,
+            "{:?}",
+            ace_king
+        );
+        assert!((ace_king.equity + queens.equity - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid_queries_are_rejected() {
+        let mut deck = Deck::seeded(1);
+        assert_eq!(
+            monte_carlo_equity(&query(&["AsKs"], ""), 10, &mut deck),
+            Err(EquityError::TooFewHands)
+        );
+        assert_eq!(
+            monte_carlo_equity(&query(&["AsKs", "AhKh"], "Ks"), 10, &mut deck),
+            Err(EquityError::DuplicateCard("Ks".parse().unwrap()))
+        );
+        assert_eq!(
+            monte_carlo_equity(
+                &query(&["AsKs", "QhQd"], "2c 3c 4c 5c 6c 7c"),
+                10,
+                &mut deck
+            ),
+            Err(EquityError::TooManyBoardCards(6))
+        );
+        assert_eq!(
+            monte_carlo_equity(&query(&["AsKs", "QhQd"], ""), 0, &mut deck),
+            Err(EquityError::NoRunouts)
+        );
+    }
+}
+
+#[cfg(test)]
+mod exact_tests {
+    use super::*;
+
+    #[test]
+    fn flush_draw_against_a_pair_on_the_turn() {
+        // Nine hearts, three aces and three kings win for AhKh
+        let result = exact_equity(&query(&["AhKh", "7c7d"], "2h 5h 9s Jd")).unwrap();
+        assert_eq!(result.runouts, 44);
+        assert_eq!(result.players[0].win, 15.0 / 44.0);
+        assert_eq!(result.players[0].equity, 15.0 / 44.0);
+        assert_eq!(result.players[1].equity, 29.0 / 44.0);
+        assert_eq!(result.players[0].std_error, 0.0);
+    }
+
+    #[test]
+    fn mirrored_hands_split_preflop() {
+        let result = exact_equity(&query(&["AsKs", "AhKh"], "")).unwrap();
+        assert_eq!(result.runouts, 1_712_304);
+        assert_eq!(result.players[0], result.players[1]);
+        assert!((result.players[0].equity - 0.5).abs() < 1e-9);
+        assert!((result.players[0].win - 0.0716).abs() < 0.001);
+    }
+
+    #[test]
+    fn random_opponents_are_enumerated() {
+        let spot = query(&["AsAd"], "Ks 7h 2c 9d").with_random_hands(1);
+        let result = exact_equity(&spot).unwrap();
+        // Every holding from the 46 unseen cards, then every river from the other 44
+        assert_eq!(result.runouts, 1035 * 44);
+        let total: f64 = result.players.iter().map(|player| player.equity).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(result.players[0].equity > 0.85);
+    }
+
+    #[test]
+    fn monte_carlo_agrees_with_exact() {
+        let spot = query(&["AsKs", "QhQd"], "Js Tc 4s");
+        let exact = calculate_equity(&spot, EquityMode::Exact).unwrap();
+        let sampled = calculate_equity(
+            &spot,
+            EquityMode::MonteCarlo {
+                runouts: 5000,
+                seed: Some(9),
+            },
+        )
+        .unwrap();
+        assert_eq!(exact.runouts, 990);
+        for (exact, sampled) in exact.players.iter().zip(&sampled.players) {
+            assert!((exact.equity - sampled.equity).abs() < 4.0 * sampled.std_error);
+        }
+    }
+
+    #[test]
+    fn too_many_random_hands_are_rejected() {
+        let spot = query(&["AsKs"], "").with_random_hands(24);
+        assert_eq!(exact_equity(&spot), Err(EquityError::NotEnoughCards));
+    }
+}
+
+#[cfg(test)]
+mod range_equity_tests {
+    use super::*;
+    use poker::range::{parse_range, Range};
+
+    fn ranges(notations: &[&str]) -> Vec<Range> {
+        notations
+            .iter()
+            .map(|notation| parse_range(notation).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn single_combos_match_hand_equity() {
+        let board = parse_cards("2h 5h 9s Jd").unwrap();
+        let result = range_equity(&ranges(&["AhKh", "7c7d"]), &board, EquityMode::Exact).unwrap();
+        assert!((result[0].equity - 15.0 / 44.0).abs() < 1e-12);
+        assert!((result[1].equity - 29.0 / 44.0).abs() < 1e-12);
+        assert_eq!(result[0].combos.len(), 1);
+        assert_eq!(result[0].combos[0].runouts, 44);
+        assert_eq!(result[0].std_error, 0.0);
+    }
+
+    #[test]
+    fn blocked_combos_are_never_dealt() {
+        let board = parse_cards("2c 7d 9h Jc").unwrap();
+        let result = range_equity(&ranges(&["AA", "AsKs"]), &board, EquityMode::Exact).unwrap();
+        let aces = &result[0];
+        assert_eq!(aces.combos.len(), 6);
+        let dealt: Vec<&ComboEquity> = aces.combos.iter().filter(|c| c.runouts > 0).collect();
+        // Three of the six pairs of aces use the ace of spades
+        assert_eq!(dealt.len(), 3);
+        let average = dealt.iter().map(|combo| combo.equity).sum::<f64>() / 3.0;
+        assert!((aces.equity - average).abs() < 1e-12);
+        assert!((aces.equity + result[1].equity - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn board_cards_remove_combos() {
+        let board = parse_cards("Kh 7d 2c").unwrap();
+        let result = range_equity(&ranges(&["KK, QQ", "AKs"]), &board, EquityMode::Exact).unwrap();
+        assert_eq!(result[0].combos.len(), 3 + 6);
+        assert_eq!(result[1].combos.len(), 3);
+    }
+
+    #[test]
+    fn weights_scale_each_combos_share() {
+        let board = parse_cards("Kh 7d 2c 4s").unwrap();
+        let full = range_equity(&ranges(&["AA, 33", "KQ"]), &board, EquityMode::Exact).unwrap();
+        let weighted =
+            range_equity(&ranges(&["AA, 33:0.5", "KQ"]), &board, EquityMode::Exact).unwrap();
+        // Making the losing pairs of threes rarer raises the range's equity
+        assert!(weighted[0].equity > full[0].equity);
+        let threes = weighted[0]
+            .combos
+            .iter()
+            .find(|combo| combo.weight == 0.5)
+            .unwrap();
+        assert_eq!(threes.cards.0.rank, threes.cards.1.rank);
+    }
+
+    #[test]
+    fn monte_carlo_agrees_with_exact() {
+        let board = parse_cards("Js Tc 4s").unwrap();
+        let spot = ranges(&["AA, KK", "QQ, AKs"]);
+        let exact = range_equity(&spot, &board, EquityMode::Exact).unwrap();
+        let sampled = range_equity(
+            &spot,
+            &board,
+            EquityMode::MonteCarlo {
+                runouts: 5000,
+                seed: Some(4),
+            },
+        )
+        .unwrap();
+        for (exact, sampled) in exact.iter().zip(&sampled) {
+            assert!(sampled.std_error > 0.0);
+            assert!((exact.equity - sampled.equity).abs() < 4.0 * sampled.std_error);
+        }
+    }
+
+    #[test]
+    fn ranges_that_cannot_be_dealt_are_rejected() {
+        let board = parse_cards("2c 7d 9h").unwrap();
+        assert_eq!(
+            range_equity(&ranges(&["AA"]), &board, EquityMode::Exact),
+            Err(EquityError::TooFewHands)
+        );
+        assert_eq!(
+            range_equity(&ranges(&["AsAh", "AhAs"]), &board, EquityMode::Exact),
+            Err(EquityError::NoMatchups)
+        );
+        let sampled = EquityMode::MonteCarlo {
+            runouts: 10,
+            seed: Some(1),
+        };
+        assert_eq!(
+            range_equity(&ranges(&["AsAh", "AhAs"]), &board, sampled),
+            Err(EquityError::NoMatchups)
+        );
+        assert_eq!(
+            range_equity(&ranges(&["22", "7d7c"]), &board, sampled),
+            Err(EquityError::NoMatchups)
+        );
+    }
+
+    #[test]
+    fn invalid_boards_are_rejected() {
+        let spot = ranges(&["AA", "KK"]);
+        let paired = parse_cards("2c 7d 2c").unwrap();
+        assert_eq!(
+            range_equity(&spot, &paired, EquityMode::Exact),
+            Err(EquityError::DuplicateCard("2c".parse().unwrap()))
+        );
+        let six_cards = parse_cards("2c 7d 9h Ts 3s 4s").unwrap();
+        assert_eq!(
+            range_equity(&spot, &six_cards, EquityMode::Exact),
+            Err(EquityError::TooManyBoardCards(6))
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use poker::card::{Card, Rank, Suit};
+use poker::evaluator::*;
+use poker::hand_utils::*;
+use poker::new_card;
+
+#[cfg(test)]
+mod evaluator_tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use std::time::Instant;
+
+    fn full_deck() -> Vec<Card> {
+        let mut deck = vec![];
+        for rank in Rank::iterator() {
+            for suit in Suit::iterator() {
+                deck.push(Card {
+                    rank: *rank,
+                    suit: *suit,
+                });
+            }
+        }
+        deck
+    }
+
+    #[test]
+    fn lookup_matches_hand_value_ordering() {
+        let mut deck = full_deck();
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut previous: Option<(u32, HandRank)> = None;
+        for _ in 0..5000 {
+            deck.shuffle(&mut rng);
+            let cards: [Card; 7] = deck[0..7].try_into().unwrap();
+            let value = evaluate_7(&cards);
+            let hand = get_hand_value(&cards);
+            assert_eq!(value, encode_hand_rank(&hand));
+            assert_eq!(hand_category(value), hand.category);
+            if let Some((previous_value, previous_hand)) = &previous {
+                assert_eq!(value.cmp(previous_value), hand.cmp(previous_hand));
+            }
+            previous = Some((value, hand));
+        }
+    }
+
+    #[test]
+    fn lookup_finds_straight_flush_over_paired_board() {
+        let cards = [
+            new_card!(Ace, Spade),
+            new_card!(Ace, Heart),
+            new_card!(Two, Spade),
+            new_card!(Three, Spade),
+            new_card!(Four, Spade),
+            new_card!(Five, Spade),
+            new_card!(Ace, Club),
+        ];
+        assert_eq!(
+            hand_category(evaluate_7(&cards)),
+            HandCategory::StraightFlush
+        );
+    }
+
+    #[test]
+    fn lookup_finds_quads_and_full_houses() {
+        let quads = [
+            new_card!(Nine, Spade),
+            new_card!(Nine, Heart),
+            new_card!(Nine, Club),
+            new_card!(Nine, Diamond),
+            new_card!(King, Spade),
+            new_card!(King, Heart),
+            new_card!(King, Club),
+        ];
+        let full_house = [
+            new_card!(Ace, Spade),
+            new_card!(Ace, Heart),
+            new_card!(Ace, Club),
+            new_card!(Queen, Diamond),
+            new_card!(Queen, Spade),
+            new_card!(Two, Heart),
+            new_card!(Three, Club),
+        ];
+        assert_eq!(hand_category(evaluate_7(&quads)), HandCategory::FourOfAKind);
+        assert_eq!(
+            hand_category(evaluate_7(&full_house)),
+            HandCategory::FullHouse
+        );
+        assert!(evaluate_7(&quads) > evaluate_7(&full_house));
+    }
+
+    // Timing depends on the machine and build profile, run with
+    // `cargo test --release -- --ignored lookup_is_much_faster`
+    #[test]
+    #[ignore]
+    fn lookup_is_much_faster_than_hand_value() {
+        let mut deck = full_deck();
+        let mut rng = StdRng::seed_from_u64(9);
+        let hands: Vec<[Card; 7]> = (0..20_000)
+            .map(|_| {
+                deck.shuffle(&mut rng);
+                deck[0..7].try_into().unwrap()
+            })
+            .collect();
+        // Builds the tables before timing
+        evaluate_7(&hands[0]);
+
+        let start = Instant::now();
+        let lookup_total: u64 = hands.iter().map(|cards| evaluate_7(cards) as u64).sum();
+        let lookup_time = start.elapsed();
+        let start = Instant::now();
+        let hand_value_total: u64 = hands
+            .iter()
+            .map(|cards| encode_hand_rank(&get_hand_value(cards)) as u64)
+            .sum();
+        let hand_value_time = start.elapsed();
+
+        assert_eq!(lookup_total, hand_value_total);
+        assert!(
+            lookup_time * 10 <= hand_value_time,
+            "evaluate_7 took {:?}, get_hand_value took {:?}",
+            lookup_time,
+            hand_value_time
+        );
+    }
+}
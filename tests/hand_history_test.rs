@@ -0,0 +1,846 @@
+mod common;
+
+use common::hand;
+use poker::card::{parse_cards, Card};
+use poker::hand_history::*;
+use poker::poker::*;
+use std::collections::HashMap;
+
+fn entry(street: Street, event: HandEvent) -> HistoryEntry {
+    HistoryEntry { street, event }
+}
+
+fn action(player: usize, action: PokerAction, amount: u64) -> HandEvent {
+    HandEvent::Action {
+        player,
+        action,
+        amount,
+        all_in: false,
+    }
+}
+
+fn board(cards: &str) -> HandEvent {
+    HandEvent::CommunityCards {
+        cards: parse_cards(cards).unwrap(),
+    }
+}
+
+fn record(starting_stacks: HashMap<usize, u64>, history: Vec<HistoryEntry>) -> HandRecord {
+    HandRecord {
+        hand_number: 7,
+        started_at: 1_704_164_645,
+        table_name: "Test".to_string(),
+        num_seats: 3,
+        button: 2,
+        small_blind: 1,
+        big_blind: 2,
+        betting_structure: BettingStructure::NoLimit,
+        names: HashMap::from([(0, "Alice".to_string())]),
+        starting_stacks,
+        history,
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+
+    #[test]
+    fn hand_won_without_showdown() {
+        use Street::*;
+        let history = vec![
+            entry(
+                Preflop,
+                HandEvent::PostBlind {
+                    player: 0,
+                    blind: Blind::Small,
+                    amount: 1,
+                },
+            ),
+            entry(
+                Preflop,
+                HandEvent::PostBlind {
+                    player: 1,
+                    blind: Blind::Big,
+                    amount: 2,
+                },
+            ),
+            entry(
+                Preflop,
+                HandEvent::HoleCards {
+                    player: 0,
+                    cards: hand("AsKs"),
+                },
+            ),
+            entry(
+                Preflop,
+                HandEvent::HoleCards {
+                    player: 1,
+                    cards: hand("QhQd"),
+                },
+            ),
+            entry(
+                Preflop,
+                HandEvent::HoleCards {
+                    player: 2,
+                    cards: hand("7c2d"),
+                },
+            ),
+            entry(Preflop, action(2, PokerAction::Fold, 0)),
+            entry(Preflop, action(0, PokerAction::Raise(4), 5)),
+            entry(Preflop, action(1, PokerAction::Call, 4)),
+            entry(Flop, board("Ah7d2s")),
+            entry(Flop, action(0, PokerAction::Raise(10), 10)),
+            entry(Flop, action(1, PokerAction::Raise(20), 30)),
+            entry(Flop, action(0, PokerAction::Call, 20)),
+            entry(Turn, board("3c")),
+            entry(Turn, action(0, PokerAction::Check, 0)),
+            entry(Turn, action(1, PokerAction::Check, 0)),
+            entry(River, board("9h")),
+            entry(
+                River,
+                HandEvent::Action {
+                    player: 0,
+                    action: PokerAction::Raise(164),
+                    amount: 164,
+                    all_in: true,
+                },
+            ),
+            entry(River, action(1, PokerAction::Fold, 0)),
+            entry(
+                River,
+                HandEvent::UncalledBetReturned {
+                    player: 0,
+                    amount: 164,
+                },
+            ),
+            entry(
+                River,
+                HandEvent::WinsPot {
+                    player: 0,
+                    pot: 0,
+                    amount: 72,
+                },
+            ),
+        ];
+        let hand = record(HashMap::from([(0, 200), (1, 200), (2, 200)]), history);
+        let expected = "\
+PokerStars Hand #7:  Hold'em No Limit (1/2) - 2024/01/02 03:04:05 UTC
+Table 'Test' 3-max Seat #3 is the button
+Seat 1: Alice (200 in chips)
+Seat 2: Player 1 (200 in chips)
+Seat 3: Player 2 (200 in chips)
+Alice: posts small blind 1
+Player 1: posts big blind 2
+*** HOLE CARDS ***
+Dealt to Alice [As Ks]
+Dealt to Player 1 [Qh Qd]
+Dealt to Player 2 [7c 2d]
+Player 2: folds
+Alice: raises 4 to 6
+Player 1: calls 4
+*** FLOP *** [Ah 7d 2s]
+Alice: bets 10
+Player 1: raises 20 to 30
+Alice: calls 20
+*** TURN *** [Ah 7d 2s] [3c]
+Alice: checks
+Player 1: checks
+*** RIVER *** [Ah 7d 2s 3c] [9h]
+Alice: bets 164 and is all-in
+Player 1: folds
+Uncalled bet (164) returned to Alice
+Alice collected 72 from pot
+Alice: doesn't show hand
+*** SUMMARY ***
+Total pot 72 | Rake 0
+Board [Ah 7d 2s 3c 9h]
+Seat 1: Alice (small blind) collected (72)
+Seat 2: Player 1 (big blind) folded on the River
+Seat 3: Player 2 (button) folded before Flop (didn't bet)
+";
+        assert_eq!(hand.to_string(), expected);
+    }
+
+    #[test]
+    fn showdown_with_a_side_pot() {
+        use Street::*;
+        let mut history = vec![
+            entry(
+                Preflop,
+                HandEvent::PostBlind {
+                    player: 0,
+                    blind: Blind::Small,
+                    amount: 1,
+                },
+            ),
+            entry(
+                Preflop,
+                HandEvent::PostBlind {
+                    player: 1,
+                    blind: Blind::Big,
+                    amount: 2,
+                },
+            ),
+        ];
+        history.extend(
+            ["AsAh", "2c3d", "KcKd"]
+                .iter()
+                .enumerate()
+                .map(|(player, cards)| {
+                    entry(
+                        Preflop,
+                        HandEvent::HoleCards {
+                            player,
+                            cards: hand(cards),
+                        },
+                    )
+                }),
+        );
+        history.extend([
+            entry(
+                Preflop,
+                HandEvent::Action {
+                    player: 2,
+                    action: PokerAction::Raise(98),
+                    amount: 100,
+                    all_in: true,
+                },
+            ),
+            entry(
+                Preflop,
+                HandEvent::Action {
+                    player: 0,
+                    action: PokerAction::Call,
+                    amount: 49,
+                    all_in: true,
+                },
+            ),
+            entry(
+                Preflop,
+                HandEvent::Action {
+                    player: 1,
+                    action: PokerAction::Call,
+                    amount: 98,
+                    all_in: true,
+                },
+            ),
+            entry(Flop, board("Qs8h5c")),
+            entry(Turn, board("9d")),
+            entry(River, board("Jc")),
+            entry(
+                River,
+                HandEvent::WinsPot {
+                    player: 0,
+                    pot: 0,
+                    amount: 150,
+                },
+            ),
+            entry(
+                River,
+                HandEvent::WinsPot {
+                    player: 2,
+                    pot: 1,
+                    amount: 100,
+                },
+            ),
+        ]);
+        let hand = record(HashMap::from([(0, 50), (1, 100), (2, 100)]), history);
+        let text = hand.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        for line in [
+            "Player 2: raises 98 to 100 and is all-in",
+            "Alice: calls 49 and is all-in",
+            "*** SHOW DOWN ***",
+            "Alice: shows [As Ah] (a pair of Aces)",
+            "Player 2: shows [Kc Kd] (a pair of Kings)",
+            "Alice collected 150 from main pot",
+            "Player 2 collected 100 from side pot-1",
+            "Total pot 250 Main pot 150. Side pot-1 100. | Rake 0",
+            "Seat 1: Alice (small blind) showed [As Ah] and won (150) with a pair of Aces",
+            "Seat 2: Player 1 (big blind) showed [2c 3d] and lost with high card Queen",
+            "Seat 3: Player 2 (button) showed [Kc Kd] and won (100) with a pair of Kings",
+        ] {
+            assert!(lines.contains(&line), "missing {:?} in\n{}", line, text);
+        }
+    }
+
+    #[test]
+    fn engine_records_every_hand() {
+        use poker::game::{GameEngine, GamePlayer};
+
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive, &passive];
+        let config = TableConfig {
+            hand_record_limit: 10,
+            ..TableConfig::default()
+        };
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(6);
+        engine.run().unwrap();
+
+        let hands = engine.hand_records();
+        assert_eq!(hands.len(), 5);
+        for (idx, hand) in hands.iter().enumerate() {
+            assert_eq!(hand.hand_number, idx as u64 + 1);
+            assert_eq!(hand.starting_stacks.values().sum::<u64>(), 600);
+        }
+        // Each hand starts with the stacks the last one finished with
+        let won: HashMap<usize, u64> = hands[0]
+            .history
+            .iter()
+            .filter_map(|entry| match entry.event {
+                HandEvent::WinsPot { player, amount, .. } => Some((player, amount)),
+                _ => None,
+            })
+            .collect();
+        for player in 0..3 {
+            assert_eq!(
+                hands[1].starting_stacks[&player],
+                198 + won.get(&player).unwrap_or(&0)
+            );
+        }
+
+        let mut out = vec![];
+        write_hands(&mut out, hands).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.matches("PokerStars Hand #").count(), 5);
+        assert_eq!(text.matches("*** SUMMARY ***").count(), 5);
+    }
+
+    /// Plays the seeded five hands of `engine_records_every_hand`
+    fn play_recorded(config: TableConfig, observer: Option<&dyn PokerObserver>) -> Vec<HandRecord> {
+        use poker::game::{GameEngine, GamePlayer};
+
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive, &passive];
+        let mut engine = PokerEngine::new(&players, config);
+        if let Some(observer) = observer {
+            engine.add_observer(observer);
+        }
+        engine.seed(6);
+        engine.run().unwrap();
+        let records = engine.take_hand_records();
+        assert!(engine.hand_records().is_empty());
+        records
+    }
+
+    #[test]
+    fn hands_are_kept_only_when_asked_for() {
+        #[derive(Default)]
+        struct Numbers {
+            seen: std::cell::RefCell<Vec<u64>>,
+        }
+        impl PokerObserver for Numbers {
+            fn hand_recorded(&self, record: &HandRecord) {
+                self.seen.borrow_mut().push(record.hand_number);
+            }
+        }
+        let numbers = Numbers::default();
+        assert!(play_recorded(TableConfig::default(), Some(&numbers)).is_empty());
+        assert_eq!(*numbers.seen.borrow(), vec![1, 2, 3, 4, 5]);
+
+        let config = TableConfig {
+            hand_record_limit: 2,
+            ..TableConfig::default()
+        };
+        let numbers: Vec<u64> = play_recorded(config, None)
+            .iter()
+            .map(|record| record.hand_number)
+            .collect();
+        assert_eq!(numbers, vec![4, 5]);
+    }
+
+    #[test]
+    fn seeded_runs_export_the_same_text() {
+        let config = TableConfig {
+            table_name: "Seeded".to_string(),
+            player_names: HashMap::from([(0, "Alice".to_string())]),
+            start_time: Some(1_700_000_000),
+            hand_record_limit: 10,
+            ..TableConfig::default()
+        };
+        let export = || {
+            let mut out = vec![];
+            write_hands(&mut out, &play_recorded(config.clone(), None)).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let text = export();
+        assert_eq!(text, export());
+        assert!(text.contains("Table 'Seeded'"));
+        assert!(text.contains("Seat 1: Alice (200 in chips)"));
+        assert!(text.contains("2023/11/14 22:13:20 UTC"));
+        assert!(text.contains("2023/11/14 22:14:20 UTC"));
+    }
+}
+
+#[cfg(test)]
+mod describe_hand_tests {
+    use super::*;
+    use poker::hand_utils::get_best_hand;
+
+    macro_rules! test_description {
+        ($name:ident, $cards:expr, $description:expr) => {
+            #[test]
+            fn $name() {
+                let hand = get_best_hand(&parse_cards($cards).unwrap()).unwrap();
+                assert_eq!(describe_hand(&hand), $description);
+            }
+        };
+    }
+
+    test_description!(high_card, "AsJd9c7h4s3d2c", "high card Ace");
+    test_description!(two_pair, "KsKd6c6h4s3d2c", "two pair, Kings and Sixes");
+    test_description!(wheel, "As2d3c4h5sJdQc", "a straight, Ace to Five");
+    test_description!(broadway, "AsKdQcJhTs3d2c", "a straight, Ten to Ace");
+    test_description!(flush, "Ah9h7h4h2hKdQc", "a flush, Ace high");
+    test_description!(
+        full_house,
+        "6s6d6cQhQs3d2c",
+        "a full house, Sixes full of Queens"
+    );
+    test_description!(quads, "9s9d9c9hAs3d2c", "four of a kind, Nines");
+    test_description!(
+        straight_flush,
+        "9h8h7h6h5hAsKd",
+        "a straight flush, Five to Nine"
+    );
+    test_description!(royal_flush, "AhKhQhJhTh2s3d", "a Royal Flush");
+}
+
+#[cfg(test)]
+mod parse_tests {
+    use super::*;
+    use poker::game::{GameEngine, GamePlayer};
+    use std::cell::RefCell;
+
+    const CASH_HAND: &str = "\
+PokerStars Hand #208340557829:  Hold'em No Limit ($0.01/$0.02 USD) - 2020/01/20 18:31:26 CET \
+[2020/01/20 12:31:26 ET]
+Table 'Aase II' 6-max Seat #4 is the button
+Seat 1: villain1 ($2.12 in chips)
+Seat 2: Hero ($2 in chips)
+Seat 3: big fish ($0.87 in chips)
+Seat 4: button guy ($3.40 in chips)
+Seat 6: away ($2 in chips) is sitting out
+villain1: posts small blind $0.01
+Hero: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to Hero [Ah Kh]
+big fish: calls $0.02
+button guy: raises $0.06 to $0.08
+villain1: folds
+Hero: raises $0.22 to $0.30
+big fish: folds
+button guy: calls $0.22
+*** FLOP *** [Kd 7s 2h]
+Hero: bets $0.40
+button guy: raises $0.80 to $1.20
+Hero: raises $0.50 to $1.70 and is all-in
+button guy: calls $0.50
+*** TURN *** [Kd 7s 2h] [9c]
+*** RIVER *** [Kd 7s 2h 9c] [3d]
+*** SHOW DOWN ***
+Hero: shows [Ah Kh] (a pair of Kings)
+button guy: shows [7d 7c] (three of a kind, Sevens)
+button guy collected $4.03 from pot
+Hero said, \"nh\"
+*** SUMMARY ***
+Total pot $4.03 | Rake $0
+Board [Kd 7s 2h 9c 3d]
+Seat 1: villain1 (small blind) folded before Flop
+Seat 2: Hero (big blind) showed [Ah Kh] and lost with a pair of Kings
+Seat 3: big fish folded before Flop
+Seat 4: button guy (button) showed [7d 7c] and won ($4.03) with three of a kind, Sevens
+Seat 6: away is sitting out
+";
+
+    fn actions(hand: &HandRecord) -> Vec<(Street, usize, PokerAction, u64)> {
+        hand.history
+            .iter()
+            .filter_map(|entry| match &entry.event {
+                HandEvent::Action {
+                    player,
+                    action,
+                    amount,
+                    ..
+                } => Some((entry.street, *player, action.clone(), *amount)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cash_hand_is_read_in_cents() {
+        let hand = parse_hand(CASH_HAND).unwrap();
+        assert_eq!(hand.hand_number, 208340557829);
+        assert_eq!(hand.table_name, "Aase II");
+        assert_eq!(hand.num_seats, 6);
+        assert_eq!(hand.button, 3);
+        assert_eq!((hand.small_blind, hand.big_blind), (1, 2));
+        assert_eq!(hand.started_at, 1_579_545_086);
+        assert_eq!(
+            hand.starting_stacks,
+            HashMap::from([(0, 212), (1, 200), (2, 87), (3, 340)])
+        );
+        assert_eq!(hand.name(5), "away");
+
+        use PokerAction::*;
+        use Street::*;
+        assert_eq!(
+            actions(&hand),
+            vec![
+                (Preflop, 2, Call, 2),
+                (Preflop, 3, Raise(6), 8),
+                (Preflop, 0, Fold, 0),
+                (Preflop, 1, Raise(22), 28),
+                (Preflop, 2, Fold, 0),
+                (Preflop, 3, Call, 22),
+                (Flop, 1, Raise(40), 40),
+                (Flop, 3, Raise(80), 120),
+                (Flop, 1, Raise(50), 130),
+                (Flop, 3, Call, 50),
+            ]
+        );
+        assert!(hand.history.contains(&entry(
+            River,
+            HandEvent::WinsPot {
+                player: 3,
+                pot: 0,
+                amount: 403,
+            }
+        )));
+    }
+
+    #[test]
+    fn hole_cards_come_from_the_deal_and_the_showdown() {
+        let record = parse_hand(CASH_HAND).unwrap();
+        let hole_cards: Vec<(usize, (Card, Card))> = record
+            .history
+            .iter()
+            .filter_map(|entry| match entry.event {
+                HandEvent::HoleCards { player, cards } => Some((player, cards)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hole_cards, vec![(1, hand("AhKh")), (3, hand("7d7c"))]);
+    }
+
+    #[test]
+    fn decisions_reject_amounts_that_do_not_add_up() {
+        let mut hand = parse_hand(CASH_HAND).unwrap();
+        hand.starting_stacks.insert(1, 5);
+        assert!(matches!(
+            hand.decisions(),
+            Err(ParseHandError::InvalidHistory(_))
+        ));
+
+        let mut hand = parse_hand(CASH_HAND).unwrap();
+        hand.starting_stacks.remove(&3);
+        assert_eq!(
+            hand.decisions().err(),
+            Some(ParseHandError::InvalidHistory(
+                "player 3 has no seat".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn decisions_rebuild_the_state_before_each_action() {
+        let record = parse_hand(CASH_HAND).unwrap();
+        let decisions = record.decisions().unwrap();
+        assert_eq!(decisions.len(), 10);
+
+        // Hero facing the raise to 8 with the big blind in
+        let three_bet = &decisions[3];
+        assert_eq!(three_bet.player, 1);
+        assert_eq!(three_bet.action, PokerAction::Raise(22));
+        assert_eq!(three_bet.state.pot, 13);
+        assert_eq!(three_bet.state.player_stacks[&1], 198);
+        assert_eq!(
+            three_bet.legal_actions,
+            LegalActions::new(6, 198, Some((6, 192)))
+        );
+        let observation = three_bet.state.observation(1);
+        assert_eq!(observation.hole_cards, hand("AhKh"));
+        assert_eq!(observation.positions[&1], Position::BigBlind);
+        assert_eq!(observation.positions[&3], Position::Button);
+
+        let shove = &decisions[8];
+        assert_eq!(shove.state.community_cards.len(), 3);
+        assert_eq!(shove.state.pot, 63 + 40 + 120);
+        assert_eq!(shove.legal_actions.all_in_amount, 130);
+    }
+
+    /// Two hands where only the hero's cards are known: a walk and a showdown the villain mucks
+    const HERO_HANDS: &str = "\
+PokerStars Hand #1001:  Hold'em No Limit (10/20) - 2024/03/01 20:15:00 UTC
+Table 'Round Trip' 6-max Seat #1 is the button
+Seat 1: button guy (1500 in chips)
+Seat 2: small (1500 in chips)
+Seat 3: Hero (1500 in chips)
+Seat 4: utg (1500 in chips)
+small: posts small blind 10
+Hero: posts big blind 20
+*** HOLE CARDS ***
+Dealt to Hero [Qs Qd]
+utg: folds
+button guy: folds
+small: folds
+Uncalled bet (10) returned to Hero
+Hero collected 20 from pot
+Hero: doesn't show hand
+*** SUMMARY ***
+Total pot 20 | Rake 0
+Seat 1: button guy (button) folded before Flop (didn't bet)
+Seat 2: small (small blind) folded before Flop
+Seat 3: Hero (big blind) collected (20)
+Seat 4: utg folded before Flop (didn't bet)
+
+
+
+PokerStars Hand #1002:  Hold'em No Limit (10/20) - 2024/03/01 20:16:00 UTC
+Table 'Round Trip' 6-max Seat #2 is the button
+Seat 1: button guy (1500 in chips)
+Seat 2: small (1490 in chips)
+Seat 3: Hero (1510 in chips)
+Seat 4: utg (1500 in chips)
+Hero: posts small blind 10
+utg: posts big blind 20
+*** HOLE CARDS ***
+Dealt to Hero [Ah Kh]
+button guy: folds
+small: folds
+Hero: raises 40 to 60
+utg: calls 40
+*** FLOP *** [Kd 7s 2h]
+Hero: bets 80
+utg: calls 80
+*** TURN *** [Kd 7s 2h] [9c]
+Hero: checks
+utg: checks
+*** RIVER *** [Kd 7s 2h 9c] [3d]
+Hero: checks
+utg: checks
+*** SHOW DOWN ***
+Hero: shows [Ah Kh] (a pair of Kings)
+utg: mucks hand
+Hero collected 280 from pot
+*** SUMMARY ***
+Total pot 280 | Rake 0
+Board [Kd 7s 2h 9c 3d]
+Seat 1: button guy folded before Flop (didn't bet)
+Seat 2: small (button) folded before Flop (didn't bet)
+Seat 3: Hero (small blind) showed [Ah Kh] and won (280) with a pair of Kings
+Seat 4: utg (big blind) mucked
+";
+
+    #[test]
+    fn hands_with_only_the_hero_known_write_and_parse_back() {
+        let parsed = parse_hands(HERO_HANDS).unwrap();
+        let mut out = vec![];
+        write_hands(&mut out, &parsed).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert!(written.contains("utg: mucks hand\n"));
+        assert!(written.contains("Seat 4: utg (big blind) mucked\n"));
+
+        let reparsed = parse_hands(&written).unwrap();
+        assert_eq!(reparsed.len(), 2);
+        for (reparsed, parsed) in reparsed.iter().zip(&parsed) {
+            assert_eq!(reparsed.history, parsed.history);
+            assert_eq!(reparsed.starting_stacks, parsed.starting_stacks);
+            assert_eq!(reparsed.button, parsed.button);
+            assert_eq!(reparsed.to_string(), parsed.to_string());
+        }
+    }
+
+    #[test]
+    fn hands_written_by_the_engine_parse_back() {
+        /// Raises the minimum before the turn, then checks and calls
+        struct MinRaiser {}
+        impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for MinRaiser {
+            fn get_action(&self, state: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+                match actions.min_raise() {
+                    Some(raise)
+                        if state.community_cards.len() < 4 && state.player.is_multiple_of(2) =>
+                    {
+                        PokerAction::Raise(raise)
+                    }
+                    _ => PassivePokerPlayer {}.get_action(state, actions),
+                }
+            }
+        }
+        let raiser = MinRaiser {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&raiser, &raiser, &raiser, &raiser];
+        let config = TableConfig {
+            ante: 1,
+            hand_record_limit: usize::MAX,
+            ..TableConfig::default()
+        };
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(8);
+        engine.run().unwrap();
+
+        let mut out = vec![];
+        write_hands(&mut out, engine.hand_records()).unwrap();
+        let parsed = parse_hands(&String::from_utf8(out).unwrap()).unwrap();
+        assert!(!parsed.is_empty());
+        assert_eq!(parsed.len(), engine.hand_records().len());
+        for (parsed, played) in parsed.iter().zip(engine.hand_records()) {
+            assert_eq!(parsed.history, played.history);
+            assert_eq!(parsed.starting_stacks, played.starting_stacks);
+            assert_eq!(parsed.button, played.button);
+            assert_eq!(parsed.started_at, played.started_at);
+            for seat in 0..4 {
+                assert_eq!(parsed.name(seat), played.name(seat));
+            }
+        }
+    }
+
+    #[test]
+    fn replay_matches_what_players_were_shown() {
+        struct Recorder {
+            seen: RefCell<Vec<(usize, u64, LegalActions, PokerAction)>>,
+        }
+        impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for Recorder {
+            fn get_action(&self, state: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+                let action = match actions.min_raise() {
+                    Some(raise) if state.community_cards.is_empty() => PokerAction::Raise(raise),
+                    _ => PassivePokerPlayer {}.get_action(state, actions),
+                };
+                self.seen.borrow_mut().push((
+                    state.player,
+                    state.pot,
+                    actions.clone(),
+                    action.clone(),
+                ));
+                action
+            }
+        }
+        let recorder = Recorder {
+            seen: RefCell::new(vec![]),
+        };
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&recorder, &recorder, &recorder];
+        let config = TableConfig {
+            hand_record_limit: 1,
+            ..TableConfig::default()
+        };
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(12);
+        engine.play_hand().unwrap();
+
+        let replayed: Vec<(usize, u64, LegalActions, PokerAction)> = engine.hand_records()[0]
+            .decisions()
+            .unwrap()
+            .into_iter()
+            .map(|decision| {
+                (
+                    decision.player,
+                    decision.state.pot,
+                    decision.legal_actions,
+                    decision.action,
+                )
+            })
+            .collect();
+        assert_eq!(replayed, *recorder.seen.borrow());
+    }
+
+    #[test]
+    fn replay_keeps_raising_closed_after_a_short_all_in() {
+        /// Raises to 10 from the button, moves the short small blind all-in and calls otherwise
+        struct ShortAllIn {
+            offered: RefCell<Vec<LegalActions>>,
+        }
+        impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for ShortAllIn {
+            fn get_action(&self, state: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+                self.offered.borrow_mut().push(actions.clone());
+                match (state.player, self.offered.borrow().len()) {
+                    (2, 1) => PokerAction::Raise(8),
+                    (0, 2) => PokerAction::Raise(4),
+                    _ => PassivePokerPlayer {}.get_action(state, actions),
+                }
+            }
+        }
+        let player = ShortAllIn {
+            offered: RefCell::new(vec![]),
+        };
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&player, &player, &player];
+        let config = TableConfig {
+            hand_record_limit: 1,
+            ..TableConfig::default()
+        };
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(12);
+        engine.set_stack(0, 14);
+        engine.play_hand().unwrap();
+
+        let replayed: Vec<LegalActions> = engine.hand_records()[0]
+            .decisions()
+            .unwrap()
+            .into_iter()
+            .map(|decision| decision.legal_actions)
+            .collect();
+        assert_eq!(replayed, *player.offered.borrow());
+        // The button calls the all-in without being offered a raise
+        assert_eq!(replayed[3].raise_range, None);
+    }
+
+    #[test]
+    fn replay_does_not_count_a_short_straddle_as_a_raise() {
+        let hand = parse_hand(
+            "\
+PokerStars Hand #2001:  Hold'em Limit (2/4) - 2024/03/01 20:15:00 UTC
+Table 'Straddle' 6-max Seat #4 is the button
+Seat 1: small (100 in chips)
+Seat 2: big (100 in chips)
+Seat 3: straddler (1 in chips)
+Seat 4: button guy (100 in chips)
+small: posts small blind 1
+big: posts big blind 2
+straddler: posts straddle 1 and is all-in
+*** HOLE CARDS ***
+button guy: raises 2 to 4
+small: raises 2 to 6
+big: raises 2 to 8
+button guy: calls 4
+small: calls 2
+*** SUMMARY ***
+",
+        )
+        .unwrap();
+        let raise_ranges: Vec<Option<(u64, u64)>> = hand
+            .decisions()
+            .unwrap()
+            .into_iter()
+            .map(|decision| decision.legal_actions.raise_range)
+            .collect();
+        // The straddle of 1 doesn't raise the big blind, so the big blind makes the fourth bet
+        assert_eq!(
+            raise_ranges,
+            vec![Some((2, 2)), Some((2, 2)), Some((2, 2)), None, None]
+        );
+    }
+
+    #[test]
+    fn text_without_a_header_is_rejected() {
+        assert_eq!(
+            parse_hand("Table 'x' 6-max Seat #1 is the button"),
+            Err(ParseHandError::MissingHeader)
+        );
+        assert_eq!(parse_hands("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn bad_cards_are_reported() {
+        let text = CASH_HAND.replace("[Ah Kh]", "[Ah Kx]");
+        assert!(matches!(
+            parse_hand(&text),
+            Err(ParseHandError::InvalidCards(_))
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::hand;
+use poker::card::parse_cards;
+use poker::hand_strength::*;
+use poker::range::parse_range;
+
+#[cfg(test)]
+mod hand_strength_tests {
+    use super::*;
+
+    #[test]
+    fn published_flop_example() {
+        // Billings et al.'s AdQc on a 3h 4c Jh flop against a random hand
+        let hole = hand("AdQc");
+        let board = parse_cards("3h 4c Jh").unwrap();
+        let strength = hand_strength(hole, &board, Opponent::Random).unwrap();
+        assert!((strength - 0.585).abs() < 0.0005, "{}", strength);
+
+        let potential = hand_potential(hole, &board, Opponent::Random, Lookahead::River).unwrap();
+        assert!(
+            (potential.positive - 0.208).abs() < 0.0005,
+            "{:?}",
+            potential
+        );
+        assert!(
+            (potential.negative - 0.274).abs() < 0.0005,
+            "{:?}",
+            potential
+        );
+
+        let effective =
+            effective_hand_strength(hole, &board, Opponent::Random, Lookahead::River).unwrap();
+        assert!((effective - (strength + (1.0 - strength) * potential.positive)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn nuts_on_the_river() {
+        let board = parse_cards("Ah Kh Qh 2c 7d").unwrap();
+        let hole = hand("JhTh");
+        assert_eq!(hand_strength(hole, &board, Opponent::Random), Ok(1.0));
+        let potential = hand_potential(hole, &board, Opponent::Random, Lookahead::River).unwrap();
+        assert_eq!(potential, HandPotential::default());
+        assert_eq!(
+            effective_hand_strength(hole, &board, Opponent::Random, Lookahead::NextStreet),
+            Ok(1.0)
+        );
+    }
+
+    #[test]
+    fn range_weighted_opponent() {
+        let board = parse_cards("Kd 8s 3c").unwrap();
+        let hole = hand("QcQs");
+        let overpairs = parse_range("AA, KK").unwrap();
+        let underpairs = parse_range("JJ-99, 77-44").unwrap();
+        let mixed = parse_range("AA, KK, JJ-99, 77-44").unwrap();
+        let against = |range| hand_strength(hole, &board, Opponent::Range(range)).unwrap();
+        assert_eq!(against(&overpairs), 0.0);
+        assert_eq!(against(&underpairs), 1.0);
+        let strength = against(&mixed);
+        // 6 aces and 3 kings beat the queens, 7 smaller pairs of 6 combos lose to them
+        assert!((strength - 42.0 / 51.0).abs() < 1e-12);
+
+        let halved = parse_range("AA:0.5, KK:0.5, JJ-99, 77-44").unwrap();
+        assert!((against(&halved) - 42.0 / 46.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn draws_have_positive_potential_on_the_next_card() {
+        let board = parse_cards("9h 8h 2c 3d").unwrap();
+        let hole = hand("AhKh");
+        let opponent = parse_range("99").unwrap();
+        let potential = hand_potential(
+            hole,
+            &board,
+            Opponent::Range(&opponent),
+            Lookahead::NextStreet,
+        )
+        .unwrap();
+        // Seven of the 44 rivers make a flush, the 2h and 3h giving the nines a full house
+        assert!(
+            (potential.positive - 7.0 / 44.0).abs() < 1e-12,
+            "{:?}",
+            potential
+        );
+        assert_eq!(potential.negative, 0.0);
+    }
+
+    #[test]
+    fn invalid_spots_are_rejected() {
+        let hole = hand("AsKs");
+        assert_eq!(
+            hand_strength(hole, &parse_cards("2c 3c").unwrap(), Opponent::Random),
+            Err(HandStrengthError::InvalidBoard(2))
+        );
+        assert_eq!(
+            hand_strength(hole, &parse_cards("As 3c 4d").unwrap(), Opponent::Random),
+            Err(HandStrengthError::DuplicateCard("As".parse().unwrap()))
+        );
+        let blocked = parse_range("AsKs").unwrap();
+        assert_eq!(
+            hand_strength(
+                hole,
+                &parse_cards("2c 3c 4d").unwrap(),
+                Opponent::Range(&blocked)
+            ),
+            Err(HandStrengthError::NoOpponentHands)
+        );
+    }
+}
@@ -147,3 +147,371 @@ mod valuation_tests {
         ),
     }
 }
+
+#[cfg(test)]
+mod hand_rank_tests {
+    use super::*;
+
+    #[test]
+    fn hand_rank_reports_category_and_kickers() {
+        let hand = get_hand_value(&[
+            new_card!(King, Heart),
+            new_card!(King, Spade),
+            new_card!(Nine, Club),
+            new_card!(Nine, Diamond),
+            new_card!(Four, Spade),
+        ]);
+        assert_eq!(hand.category, HandCategory::TwoPair);
+        assert_eq!(hand.kickers, vec![Rank::King, Rank::Nine, Rank::Four]);
+        assert_eq!(hand.cards.len(), 5);
+    }
+
+    #[test]
+    fn wheel_is_a_five_high_straight() {
+        let hand = get_hand_value(&[
+            new_card!(Ace, Heart),
+            new_card!(Two, Spade),
+            new_card!(Three, Club),
+            new_card!(Four, Diamond),
+            new_card!(Five, Spade),
+        ]);
+        assert_eq!(hand.category, HandCategory::Straight);
+        assert_eq!(hand.kickers, vec![Rank::Five]);
+    }
+
+    #[test]
+    fn equal_hands_in_different_suits_compare_equal() {
+        let spades = get_hand_value(&[
+            new_card!(Ace, Spade),
+            new_card!(King, Spade),
+            new_card!(Nine, Club),
+            new_card!(Seven, Diamond),
+            new_card!(Four, Spade),
+        ]);
+        let hearts = get_hand_value(&[
+            new_card!(Ace, Heart),
+            new_card!(King, Heart),
+            new_card!(Nine, Diamond),
+            new_card!(Seven, Club),
+            new_card!(Four, Heart),
+        ]);
+        assert_eq!(spades, hearts);
+    }
+}
+
+#[cfg(test)]
+mod best_hand_tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    fn ranks(cards: &[Card]) -> Vec<Rank> {
+        let mut ranks: Vec<Rank> = cards.iter().map(|card| card.rank).collect();
+        ranks.sort();
+        ranks.reverse();
+        ranks
+    }
+
+    macro_rules! test_best_hand {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (cards, category, kickers, hand_ranks): (
+                    Vec<Card>,
+                    HandCategory,
+                    Vec<Rank>,
+                    Vec<Rank>,
+                ) = $value;
+                let hand = get_best_hand(&cards).unwrap();
+                assert_eq!(hand.category, category);
+                assert_eq!(hand.kickers, kickers);
+                assert_eq!(hand.cards.len(), 5);
+                assert_eq!(ranks(&hand.cards), hand_ranks);
+            }
+        )*
+        }
+    }
+
+    test_best_hand! {
+        high_card_uses_top_five: (
+            vec![
+                new_card!(Ace, Heart),
+                new_card!(Jack, Spade),
+                new_card!(Nine, Club),
+                new_card!(Seven, Diamond),
+                new_card!(Five, Spade),
+                new_card!(Four, Heart),
+                new_card!(Two, Club),
+            ],
+            HandCategory::HighCard,
+            vec![Rank::Ace, Rank::Jack, Rank::Nine, Rank::Seven, Rank::Five],
+            vec![Rank::Ace, Rank::Jack, Rank::Nine, Rank::Seven, Rank::Five],
+        ),
+        pair_kickers_are_the_highest_cards: (
+            vec![
+                new_card!(Two, Heart),
+                new_card!(Three, Spade),
+                new_card!(Eight, Club),
+                new_card!(Eight, Diamond),
+                new_card!(King, Spade),
+                new_card!(Ten, Heart),
+                new_card!(Queen, Club),
+            ],
+            HandCategory::Pair,
+            vec![Rank::Eight, Rank::King, Rank::Queen, Rank::Ten],
+            vec![Rank::King, Rank::Queen, Rank::Ten, Rank::Eight, Rank::Eight],
+        ),
+        two_pair_from_three_pairs_uses_third_pair_as_kicker: (
+            vec![
+                new_card!(Two, Heart),
+                new_card!(Two, Spade),
+                new_card!(Six, Club),
+                new_card!(Six, Diamond),
+                new_card!(Nine, Spade),
+                new_card!(Nine, Heart),
+                new_card!(Four, Club),
+            ],
+            HandCategory::TwoPair,
+            vec![Rank::Nine, Rank::Six, Rank::Four],
+            vec![Rank::Nine, Rank::Nine, Rank::Six, Rank::Six, Rank::Four],
+        ),
+        two_pair_third_pair_beats_lower_kicker: (
+            vec![
+                new_card!(Two, Heart),
+                new_card!(Ten, Spade),
+                new_card!(Six, Club),
+                new_card!(Six, Diamond),
+                new_card!(Nine, Spade),
+                new_card!(Nine, Heart),
+                new_card!(Ten, Club),
+            ],
+            HandCategory::TwoPair,
+            vec![Rank::Ten, Rank::Nine, Rank::Six],
+            vec![Rank::Ten, Rank::Ten, Rank::Nine, Rank::Nine, Rank::Six],
+        ),
+        set_kickers_are_the_highest_cards: (
+            vec![
+                new_card!(Two, Heart),
+                new_card!(Five, Spade),
+                new_card!(Five, Club),
+                new_card!(Five, Diamond),
+                new_card!(Jack, Spade),
+                new_card!(Three, Heart),
+                new_card!(Ace, Club),
+            ],
+            HandCategory::ThreeOfAKind,
+            vec![Rank::Five, Rank::Ace, Rank::Jack],
+            vec![Rank::Ace, Rank::Jack, Rank::Five, Rank::Five, Rank::Five],
+        ),
+        straight_ignores_paired_ranks: (
+            vec![
+                new_card!(Nine, Heart),
+                new_card!(Eight, Spade),
+                new_card!(Eight, Club),
+                new_card!(Seven, Diamond),
+                new_card!(Six, Spade),
+                new_card!(Five, Heart),
+                new_card!(Two, Club),
+            ],
+            HandCategory::Straight,
+            vec![Rank::Nine],
+            vec![Rank::Nine, Rank::Eight, Rank::Seven, Rank::Six, Rank::Five],
+        ),
+        highest_straight_is_chosen: (
+            vec![
+                new_card!(Ace, Heart),
+                new_card!(Two, Spade),
+                new_card!(Three, Club),
+                new_card!(Four, Diamond),
+                new_card!(Five, Spade),
+                new_card!(Six, Heart),
+                new_card!(Seven, Club),
+            ],
+            HandCategory::Straight,
+            vec![Rank::Seven],
+            vec![Rank::Seven, Rank::Six, Rank::Five, Rank::Four, Rank::Three],
+        ),
+        wheel_plays_ace_low: (
+            vec![
+                new_card!(Ace, Heart),
+                new_card!(Two, Spade),
+                new_card!(Three, Club),
+                new_card!(Four, Diamond),
+                new_card!(Five, Spade),
+                new_card!(King, Heart),
+                new_card!(King, Club),
+            ],
+            HandCategory::Straight,
+            vec![Rank::Five],
+            vec![Rank::Ace, Rank::Five, Rank::Four, Rank::Three, Rank::Two],
+        ),
+        flush_uses_top_five_of_suit: (
+            vec![
+                new_card!(Ace, Heart),
+                new_card!(Two, Heart),
+                new_card!(Nine, Heart),
+                new_card!(Four, Heart),
+                new_card!(Jack, Heart),
+                new_card!(Six, Heart),
+                new_card!(King, Club),
+            ],
+            HandCategory::Flush,
+            vec![Rank::Ace, Rank::Jack, Rank::Nine, Rank::Six, Rank::Four],
+            vec![Rank::Ace, Rank::Jack, Rank::Nine, Rank::Six, Rank::Four],
+        ),
+        full_house_from_two_sets: (
+            vec![
+                new_card!(Four, Heart),
+                new_card!(Four, Spade),
+                new_card!(Four, Club),
+                new_card!(Queen, Diamond),
+                new_card!(Queen, Spade),
+                new_card!(Queen, Heart),
+                new_card!(Ace, Club),
+            ],
+            HandCategory::FullHouse,
+            vec![Rank::Queen, Rank::Four],
+            vec![Rank::Queen, Rank::Queen, Rank::Queen, Rank::Four, Rank::Four],
+        ),
+        full_house_uses_highest_pair: (
+            vec![
+                new_card!(Four, Heart),
+                new_card!(Four, Spade),
+                new_card!(Four, Club),
+                new_card!(Two, Diamond),
+                new_card!(Two, Spade),
+                new_card!(Ten, Heart),
+                new_card!(Ten, Club),
+            ],
+            HandCategory::FullHouse,
+            vec![Rank::Four, Rank::Ten],
+            vec![Rank::Ten, Rank::Ten, Rank::Four, Rank::Four, Rank::Four],
+        ),
+        quads_kicker_can_come_from_a_pair: (
+            vec![
+                new_card!(Seven, Heart),
+                new_card!(Seven, Spade),
+                new_card!(Seven, Club),
+                new_card!(Seven, Diamond),
+                new_card!(Three, Spade),
+                new_card!(Jack, Heart),
+                new_card!(Jack, Club),
+            ],
+            HandCategory::FourOfAKind,
+            vec![Rank::Seven, Rank::Jack],
+            vec![Rank::Jack, Rank::Seven, Rank::Seven, Rank::Seven, Rank::Seven],
+        ),
+        straight_flush_with_paired_board: (
+            vec![
+                new_card!(Nine, Spade),
+                new_card!(Nine, Club),
+                new_card!(Eight, Spade),
+                new_card!(Seven, Spade),
+                new_card!(Six, Spade),
+                new_card!(Five, Spade),
+                new_card!(Ten, Heart),
+            ],
+            HandCategory::StraightFlush,
+            vec![Rank::Nine],
+            vec![Rank::Nine, Rank::Eight, Rank::Seven, Rank::Six, Rank::Five],
+        ),
+        straight_flush_beats_higher_straight: (
+            vec![
+                new_card!(Ten, Heart),
+                new_card!(Nine, Club),
+                new_card!(Eight, Club),
+                new_card!(Seven, Club),
+                new_card!(Six, Club),
+                new_card!(Five, Club),
+                new_card!(Jack, Diamond),
+            ],
+            HandCategory::StraightFlush,
+            vec![Rank::Nine],
+            vec![Rank::Nine, Rank::Eight, Rank::Seven, Rank::Six, Rank::Five],
+        ),
+        steel_wheel: (
+            vec![
+                new_card!(Ace, Diamond),
+                new_card!(Two, Diamond),
+                new_card!(Three, Diamond),
+                new_card!(Four, Diamond),
+                new_card!(Five, Diamond),
+                new_card!(Ace, Club),
+                new_card!(Ace, Heart),
+            ],
+            HandCategory::StraightFlush,
+            vec![Rank::Five],
+            vec![Rank::Ace, Rank::Five, Rank::Four, Rank::Three, Rank::Two],
+        ),
+    }
+
+    #[test]
+    fn quads_without_kicker_does_not_panic() {
+        let hand = get_hand_value(&[
+            new_card!(Seven, Heart),
+            new_card!(Seven, Spade),
+            new_card!(Seven, Club),
+            new_card!(Seven, Diamond),
+        ]);
+        assert_eq!(hand.category, HandCategory::FourOfAKind);
+        assert_eq!(hand.kickers, vec![Rank::Seven]);
+    }
+
+    #[test]
+    fn best_hand_rejects_invalid_card_counts() {
+        let cards: Vec<Card> = Rank::iterator()
+            .map(|rank| Card {
+                rank: *rank,
+                suit: Suit::Heart,
+            })
+            .collect();
+        assert!(get_best_hand(&cards[0..4]).is_none());
+        assert!(get_best_hand(&cards[0..8]).is_none());
+    }
+
+    fn five_card_subsets(cards: &[Card]) -> Vec<Vec<Card>> {
+        let mut subsets = vec![];
+        for skip_a in 0..cards.len() {
+            for skip_b in skip_a + 1..cards.len() {
+                subsets.push(
+                    cards
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| *idx != skip_a && *idx != skip_b)
+                        .map(|(_, card)| *card)
+                        .collect(),
+                );
+            }
+        }
+        subsets
+    }
+
+    #[test]
+    fn seven_card_value_matches_best_five_card_subset() {
+        let mut deck: Vec<Card> = vec![];
+        for rank in Rank::iterator() {
+            for suit in Suit::iterator() {
+                deck.push(Card {
+                    rank: *rank,
+                    suit: *suit,
+                });
+            }
+        }
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..2000 {
+            deck.shuffle(&mut rng);
+            let cards = &deck[0..7];
+            let best = get_best_hand(cards).unwrap();
+            let brute_force = five_card_subsets(cards)
+                .iter()
+                .map(|subset| get_hand_value(subset))
+                .max()
+                .unwrap();
+            assert_eq!(best, brute_force);
+            // The chosen cards must make the reported hand on their own
+            assert_eq!(get_hand_value(&best.cards), best);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use log::kv::Key;
+use log::{Level, Log, Metadata, Record};
+use poker::game::GamePlayer;
+use poker::poker::*;
+use std::sync::Mutex;
+
+#[cfg(test)]
+mod logging_tests {
+    use super::*;
+
+    struct RecordingLogger {
+        records: Mutex<Vec<(Level, String, Option<u64>)>>,
+    }
+
+    impl Log for RecordingLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            let players = record
+                .key_values()
+                .get(Key::from("players"))
+                .and_then(|value| value.to_u64());
+            self.records
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string(), players));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: RecordingLogger = RecordingLogger {
+        records: Mutex::new(vec![]),
+    };
+
+    #[test]
+    fn engine_reports_through_the_log_facade() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive];
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.seed(2);
+        engine.play_hand().unwrap();
+
+        let records = LOGGER.records.lock().unwrap();
+        assert!(records.contains(&(Level::Debug, "created poker engine".to_string(), Some(2))));
+        assert!(records.contains(&(Level::Debug, "starting hand".to_string(), Some(2))));
+        assert!(records
+            .iter()
+            .any(|(level, message, _)| *level == Level::Trace && message == "evaluated hand"));
+        assert!(records
+            .iter()
+            .any(|(level, message, _)| *level == Level::Debug && message == "awarded pot"));
+    }
+}
@@ -0,0 +1,1482 @@
+use poker::poker::*;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(test)]
+mod pot_tests {
+    use super::*;
+
+    #[test]
+    fn equal_contributions_make_a_single_pot() {
+        let contributions = HashMap::from([(0, 50), (1, 50), (2, 50)]);
+        let pots = build_pots(&contributions, &HashSet::new());
+        assert_eq!(
+            pots,
+            vec![Pot {
+                amount: 150,
+                eligible_players: vec![0, 1, 2],
+            }]
+        );
+    }
+
+    #[test]
+    fn short_all_in_creates_side_pot() {
+        let contributions = HashMap::from([(0, 20), (1, 100), (2, 100)]);
+        let pots = build_pots(&contributions, &HashSet::new());
+        assert_eq!(
+            pots,
+            vec![
+                Pot {
+                    amount: 60,
+                    eligible_players: vec![0, 1, 2],
+                },
+                Pot {
+                    amount: 160,
+                    eligible_players: vec![1, 2],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn multiple_all_ins_create_ordered_side_pots() {
+        let contributions = HashMap::from([(0, 10), (1, 40), (2, 100), (3, 100)]);
+        let pots = build_pots(&contributions, &HashSet::new());
+        let amounts: Vec<u64> = pots.iter().map(|pot| pot.amount).collect();
+        assert_eq!(amounts, vec![40, 90, 120]);
+        assert_eq!(pots[0].eligible_players, vec![0, 1, 2, 3]);
+        assert_eq!(pots[1].eligible_players, vec![1, 2, 3]);
+        assert_eq!(pots[2].eligible_players, vec![2, 3]);
+    }
+
+    #[test]
+    fn folded_players_contribute_but_are_not_eligible() {
+        let contributions = HashMap::from([(0, 30), (1, 10), (2, 60), (3, 60)]);
+        let folded = HashSet::from([0]);
+        let pots = build_pots(&contributions, &folded);
+        assert_eq!(
+            pots,
+            vec![
+                Pot {
+                    amount: 40,
+                    eligible_players: vec![1, 2, 3],
+                },
+                Pot {
+                    amount: 120,
+                    eligible_players: vec![2, 3],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pots_hold_every_chip_contributed() {
+        let contributions = HashMap::from([(0, 200), (1, 35), (2, 120), (3, 7)]);
+        let folded = HashSet::from([0]);
+        let pots = build_pots(&contributions, &folded);
+        let total: u64 = pots.iter().map(|pot| pot.amount).sum();
+        assert_eq!(total, 362);
+    }
+}
+
+#[cfg(test)]
+mod seed_tests {
+    use super::*;
+    use poker::card::Card;
+    use poker::game::{GameEngine, GamePlayer};
+    use std::cell::RefCell;
+
+    /// A passive player that remembers every hole card it is shown
+    struct RecordingPlayer {
+        seen: RefCell<Vec<(Card, Card)>>,
+    }
+
+    impl RecordingPlayer {
+        fn new() -> Self {
+            RecordingPlayer {
+                seen: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for RecordingPlayer {
+        fn get_action(&self, state: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+            self.seen.borrow_mut().push(state.hole_cards);
+            PassivePokerPlayer {}.get_action(state, actions)
+        }
+    }
+
+    fn play_seeded(seed: u64) -> (Vec<(Card, Card)>, HashMap<usize, u64>) {
+        let player1 = RecordingPlayer::new();
+        let player2 = RecordingPlayer::new();
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&player1, &player2];
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.seed(seed);
+        engine.run().unwrap();
+        let stacks = engine.state().player_stacks.clone();
+        let mut seen = player1.seen.take();
+        seen.append(&mut player2.seen.take());
+        (seen, stacks)
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_game() {
+        assert_eq!(play_seeded(7), play_seeded(7));
+    }
+
+    #[test]
+    fn different_seeds_deal_different_games() {
+        assert_ne!(play_seeded(7).0, play_seeded(8).0);
+    }
+}
+
+#[cfg(test)]
+mod table_config_tests {
+    use super::*;
+    use poker::game::{GameEngine, GamePlayer};
+
+    /// Folds whenever facing a bet
+    struct FoldingPlayer {}
+
+    impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for FoldingPlayer {
+        fn get_action(&self, _: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+            if actions.can_fold {
+                PokerAction::Fold
+            } else {
+                PokerAction::Check
+            }
+        }
+    }
+
+    fn stacks_after_one_hand(num_players: usize, config: TableConfig) -> Vec<u64> {
+        let folder = FoldingPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            (0..num_players).map(|_| &folder as _).collect();
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(1);
+        engine.play_hand().unwrap();
+        (0..num_players)
+            .map(|player| engine.state().player_stacks[&player])
+            .collect()
+    }
+
+    #[test]
+    fn default_blinds_are_one_and_two() {
+        assert_eq!(
+            stacks_after_one_hand(3, TableConfig::default()),
+            vec![199, 201, 200]
+        );
+    }
+
+    #[test]
+    fn configured_blinds_and_antes_are_posted() {
+        let config = TableConfig {
+            small_blind: 5,
+            big_blind: 10,
+            ante: 1,
+            ..TableConfig::default()
+        };
+        assert_eq!(stacks_after_one_hand(3, config), vec![194, 207, 199]);
+    }
+
+    #[test]
+    fn big_blind_ante_is_posted_by_the_big_blind() {
+        let config = TableConfig {
+            big_blind_ante: 3,
+            ..TableConfig::default()
+        };
+        assert_eq!(stacks_after_one_hand(3, config), vec![199, 201, 200]);
+    }
+
+    #[test]
+    fn straddle_acts_last_and_wins_the_blinds() {
+        let config = TableConfig {
+            straddle: Some(4),
+            ..TableConfig::default()
+        };
+        assert_eq!(stacks_after_one_hand(4, config), vec![199, 198, 203, 200]);
+    }
+
+    #[test]
+    fn short_stacked_player_does_not_straddle() {
+        let config = TableConfig {
+            straddle: Some(4),
+            ..TableConfig::default()
+        };
+        let folder = FoldingPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&folder, &folder, &folder, &folder];
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(1);
+        engine.set_stack(2, 2);
+        engine.play_hand().unwrap();
+        assert!(!engine.state().history.iter().any(|entry| matches!(
+            entry.event,
+            HandEvent::PostBlind {
+                blind: Blind::Straddle,
+                ..
+            }
+        )));
+        // Without a straddle the short stack folds first and the big blind wins the blinds
+        let stacks: Vec<u64> = (0..4)
+            .map(|player| engine.state().player_stacks[&player])
+            .collect();
+        assert_eq!(stacks, vec![199, 201, 2, 200]);
+    }
+
+    #[test]
+    fn straddle_needs_three_players() {
+        let config = TableConfig {
+            straddle: Some(4),
+            ..TableConfig::default()
+        };
+        assert_eq!(stacks_after_one_hand(2, config), vec![199, 201]);
+    }
+
+    #[test]
+    fn chips_are_conserved_with_forced_bets() {
+        let config = TableConfig {
+            small_blind: 2,
+            big_blind: 5,
+            ante: 1,
+            big_blind_ante: 2,
+            straddle: Some(10),
+            starting_stack: 50,
+            ..TableConfig::default()
+        };
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive, &passive, &passive];
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(11);
+        engine.run().unwrap();
+        let total: u64 = engine.state().player_stacks.values().sum();
+        assert_eq!(total, 200);
+    }
+
+    #[test]
+    fn too_many_players_exhaust_the_deck() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            (0..23).map(|_| &passive as _).collect();
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.seed(1);
+        assert_eq!(engine.play_hand(), Err(PokerError::DeckExhausted));
+        assert!(engine.hand_records().is_empty());
+        assert!(engine
+            .state()
+            .player_stacks
+            .values()
+            .all(|stack| *stack == 200));
+
+        // Twenty two players use every card in the deck
+        let players = &players[..22].to_vec();
+        let mut engine = PokerEngine::new(players, TableConfig::default());
+        engine.seed(1);
+        assert_eq!(engine.play_hand(), Ok(()));
+    }
+}
+
+#[cfg(test)]
+mod betting_structure_tests {
+    use super::*;
+    use poker::game::GamePlayer;
+
+    fn situation(pot: u64, to_call: u64, stack: u64, last_raise: u64) -> BettingSituation {
+        BettingSituation {
+            pot,
+            to_call,
+            stack,
+            last_raise,
+            big_blind: 2,
+            big_bet_street: false,
+            bets: 1,
+        }
+    }
+
+    #[test]
+    fn no_limit_min_raise_is_last_raise_and_max_is_all_in() {
+        let bounds = BettingStructure::NoLimit.raise_bounds(&situation(30, 10, 100, 10));
+        assert_eq!(bounds, Some((10, 90)));
+    }
+
+    #[test]
+    fn no_limit_opening_bet_is_at_least_big_blind() {
+        let bounds = BettingStructure::NoLimit.raise_bounds(&situation(30, 0, 100, 0));
+        assert_eq!(bounds, Some((2, 100)));
+    }
+
+    #[test]
+    fn short_stack_may_only_raise_all_in() {
+        let bounds = BettingStructure::NoLimit.raise_bounds(&situation(30, 10, 15, 10));
+        assert_eq!(bounds, Some((5, 5)));
+        let bounds = BettingStructure::NoLimit.raise_bounds(&situation(30, 10, 10, 10));
+        assert_eq!(bounds, None);
+    }
+
+    #[test]
+    fn pot_limit_max_is_pot_after_calling() {
+        // Blinds of 1 and 2, small blind to act: calling makes the pot 4
+        let bounds = BettingStructure::PotLimit.raise_bounds(&situation(3, 1, 200, 2));
+        assert_eq!(bounds, Some((2, 4)));
+        let bounds = BettingStructure::PotLimit.raise_bounds(&situation(300, 50, 120, 50));
+        assert_eq!(bounds, Some((50, 70)));
+    }
+
+    #[test]
+    fn fixed_limit_uses_small_and_big_bets_up_to_the_cap() {
+        let structure = BettingStructure::FixedLimit {
+            small_bet: 2,
+            big_bet: 4,
+            raise_cap: 4,
+        };
+        let mut small_bet_street = situation(10, 2, 100, 2);
+        assert_eq!(structure.raise_bounds(&small_bet_street), Some((2, 2)));
+        let big_bet_street = BettingSituation {
+            big_bet_street: true,
+            ..small_bet_street.clone()
+        };
+        assert_eq!(structure.raise_bounds(&big_bet_street), Some((4, 4)));
+        small_bet_street.bets = 4;
+        assert_eq!(structure.raise_bounds(&small_bet_street), None);
+    }
+
+    /// The raise amount of every raise in the last hand, with its street
+    fn raises(engine: &PokerEngine) -> Vec<(Street, u64)> {
+        engine
+            .state()
+            .history
+            .iter()
+            .filter_map(|entry| match entry.event {
+                HandEvent::Action {
+                    action: PokerAction::Raise(amount),
+                    ..
+                } => Some((entry.street, amount)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Raises as much as it is allowed to, calls otherwise
+    struct AggressivePlayer {}
+
+    impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for AggressivePlayer {
+        fn get_action(&self, _: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+            match actions.max_raise() {
+                Some(value) => PokerAction::Raise(value),
+                None if actions.call_amount.is_some() => PokerAction::Call,
+                None => PokerAction::Check,
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_limit_hand_is_capped_every_street() {
+        let config = TableConfig {
+            betting_structure: BettingStructure::FixedLimit {
+                small_bet: 2,
+                big_bet: 4,
+                raise_cap: 4,
+            },
+            ..TableConfig::default()
+        };
+        let aggressive = AggressivePlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&aggressive, &aggressive];
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(3);
+        engine.play_hand().unwrap();
+        // Four bets of 2 on the first two streets and of 4 on the last two, the big blind being
+        // the first bet preflop
+        let mut expected = vec![(Street::Preflop, 2); 3];
+        expected.extend([(Street::Flop, 2); 4]);
+        expected.extend([(Street::Turn, 4); 4]);
+        expected.extend([(Street::River, 4); 4]);
+        assert_eq!(raises(&engine), expected);
+        let mut stacks: Vec<u64> = engine.state().player_stacks.values().copied().collect();
+        stacks.sort();
+        assert_eq!(stacks, vec![152, 248]);
+    }
+
+    #[test]
+    fn pot_limit_bets_are_pot_sized_at_most() {
+        /// Bets the pot whenever it can, calls otherwise
+        struct PotBetter {}
+        impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for PotBetter {
+            fn get_action(&self, state: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+                if actions.call_amount.is_some() {
+                    PokerAction::Call
+                } else {
+                    assert!(actions.max_raise().unwrap() <= state.pot);
+                    AggressivePlayer {}.get_action(state, actions)
+                }
+            }
+        }
+        let config = TableConfig {
+            betting_structure: BettingStructure::PotLimit,
+            ..TableConfig::default()
+        };
+        let pot_better = PotBetter {};
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&pot_better, &passive];
+        let mut engine = PokerEngine::new(&players, config);
+        engine.seed(5);
+        engine.play_hand().unwrap();
+        // Preflop 2 each, then a pot sized bet on every street: 4, 12 and 36 each
+        assert_eq!(
+            raises(&engine),
+            vec![(Street::Flop, 4), (Street::Turn, 12), (Street::River, 36)]
+        );
+        let mut stacks: Vec<u64> = engine.state().player_stacks.values().copied().collect();
+        stacks.sort();
+        assert_eq!(stacks, vec![146, 254]);
+    }
+}
+
+#[cfg(test)]
+mod illegal_action_tests {
+    use super::*;
+    use poker::game::{GameEngine, GamePlayer};
+    use std::cell::Cell;
+
+    /// Checks even when facing a bet, and only calls after `mistakes` illegal checks
+    struct CheckingPlayer {
+        mistakes: u32,
+        asked: Cell<u32>,
+    }
+
+    impl CheckingPlayer {
+        fn new(mistakes: u32) -> Self {
+            CheckingPlayer {
+                mistakes,
+                asked: Cell::new(0),
+            }
+        }
+    }
+
+    impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for CheckingPlayer {
+        fn get_action(&self, _: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+            self.asked.set(self.asked.get() + 1);
+            if actions.call_amount.is_some() && self.asked.get() > self.mistakes {
+                PokerAction::Call
+            } else {
+                PokerAction::Check
+            }
+        }
+    }
+
+    fn engine_with_policy<'a>(
+        players: &'a Vec<&'a dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>>,
+        policy: IllegalActionPolicy,
+    ) -> PokerEngine<'a> {
+        let config = TableConfig {
+            illegal_action_policy: policy,
+            ..TableConfig::default()
+        };
+        let mut engine = PokerEngine::new(players, config);
+        engine.seed(2);
+        engine
+    }
+
+    #[test]
+    fn validation_reports_the_violation() {
+        let checker = CheckingPlayer::new(u32::MAX);
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&checker, &passive];
+        let mut engine = engine_with_policy(&players, IllegalActionPolicy::Abort);
+        assert_eq!(
+            engine.play_hand(),
+            Err(PokerError::IllegalAction {
+                player: 0,
+                action: PokerAction::Check,
+                reason: IllegalActionReason::NotAvailable,
+            })
+        );
+    }
+
+    #[test]
+    fn abort_returns_every_chip_bet_in_the_hand() {
+        let checker = CheckingPlayer::new(u32::MAX);
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&checker, &passive];
+        let mut engine = engine_with_policy(&players, IllegalActionPolicy::Abort);
+        assert!(engine.run().is_err());
+        assert_eq!(engine.state().player_stacks[&0], 200);
+        assert_eq!(engine.state().player_stacks[&1], 200);
+        assert_eq!(engine.state().pot, 0);
+    }
+
+    #[test]
+    fn fold_policy_folds_the_offending_player() {
+        let checker = CheckingPlayer::new(u32::MAX);
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&checker, &passive];
+        let mut engine = engine_with_policy(&players, IllegalActionPolicy::Fold);
+        engine.play_hand().unwrap();
+        // The small blind is folded and loses their blind
+        assert_eq!(engine.state().player_stacks[&0], 199);
+        assert_eq!(engine.state().player_stacks[&1], 201);
+        assert_eq!(engine.illegal_actions().len(), 1);
+    }
+
+    #[test]
+    fn reask_policy_gives_the_player_another_chance() {
+        let checker = CheckingPlayer::new(2);
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&checker, &passive];
+        let mut engine =
+            engine_with_policy(&players, IllegalActionPolicy::Reask { max_attempts: 3 });
+        engine.play_hand().unwrap();
+        // The third answer was a legal call so the hand went to showdown
+        assert_eq!(engine.illegal_actions().len(), 2);
+        assert_eq!(engine.state().player_stacks.values().sum::<u64>(), 400);
+        assert_ne!(engine.state().player_stacks[&0], 199);
+    }
+
+    #[test]
+    fn reask_policy_folds_after_too_many_attempts() {
+        let checker = CheckingPlayer::new(u32::MAX);
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&checker, &passive];
+        let mut engine =
+            engine_with_policy(&players, IllegalActionPolicy::Reask { max_attempts: 3 });
+        engine.play_hand().unwrap();
+        assert_eq!(engine.illegal_actions().len(), 3);
+        assert_eq!(engine.state().player_stacks[&0], 199);
+    }
+
+    #[test]
+    fn raises_outside_the_bounds_are_illegal() {
+        struct OverBetter {}
+        impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for OverBetter {
+            fn get_action(&self, _: &PlayerObservation, _: &LegalActions) -> PokerAction {
+                PokerAction::Raise(1000)
+            }
+        }
+        let over_better = OverBetter {};
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&over_better, &passive];
+        let mut engine = engine_with_policy(&players, IllegalActionPolicy::Abort);
+        assert_eq!(
+            engine.play_hand(),
+            Err(PokerError::IllegalAction {
+                player: 0,
+                action: PokerAction::Raise(1000),
+                reason: IllegalActionReason::RaiseTooLarge { max: 198 },
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod legal_actions_tests {
+    use super::*;
+    use poker::game::GamePlayer;
+    use std::cell::RefCell;
+
+    #[test]
+    fn facing_a_bet() {
+        let legal = LegalActions::new(10, 100, Some((10, 90)));
+        assert!(legal.can_fold);
+        assert!(!legal.can_check);
+        assert_eq!(legal.call_amount, Some(10));
+        assert_eq!(legal.min_raise(), Some(10));
+        assert_eq!(legal.max_raise(), Some(90));
+        assert_eq!(legal.all_in_amount, 100);
+        assert!(legal.is_legal(&PokerAction::Raise(50)));
+        assert_eq!(
+            legal.validate(&PokerAction::Check),
+            Err(IllegalActionReason::NotAvailable)
+        );
+        assert_eq!(
+            legal.validate(&PokerAction::Raise(5)),
+            Err(IllegalActionReason::RaiseTooSmall { min: 10 })
+        );
+        assert_eq!(
+            legal.validate(&PokerAction::Raise(95)),
+            Err(IllegalActionReason::RaiseTooLarge { max: 90 })
+        );
+    }
+
+    #[test]
+    fn calling_for_less_than_the_bet() {
+        let legal = LegalActions::new(50, 30, None);
+        assert_eq!(legal.call_amount, Some(30));
+        assert_eq!(
+            legal.validate(&PokerAction::Raise(10)),
+            Err(IllegalActionReason::NotAvailable)
+        );
+    }
+
+    #[test]
+    fn nothing_to_call() {
+        let legal = LegalActions::new(0, 100, Some((2, 100)));
+        assert!(legal.can_check);
+        assert!(!legal.can_fold);
+        assert_eq!(legal.call_amount, None);
+        assert!(!legal.is_legal(&PokerAction::Call));
+        assert!(!legal.is_legal(&PokerAction::Fold));
+    }
+
+    struct RecordingPlayer {
+        seen: RefCell<Vec<LegalActions>>,
+    }
+
+    impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for RecordingPlayer {
+        fn get_action(&self, state: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+            self.seen.borrow_mut().push(actions.clone());
+            PassivePokerPlayer {}.get_action(state, actions)
+        }
+    }
+
+    #[test]
+    fn engine_describes_the_small_blind_options() {
+        let recorder = RecordingPlayer {
+            seen: RefCell::new(vec![]),
+        };
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&recorder, &passive];
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.seed(1);
+        engine.play_hand().unwrap();
+        assert_eq!(
+            recorder.seen.borrow()[0],
+            LegalActions {
+                can_fold: true,
+                can_check: false,
+                call_amount: Some(1),
+                raise_range: Some((2, 198)),
+                all_in_amount: 199,
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod observation_tests {
+    use super::*;
+    use poker::card::{parse_cards, Card};
+
+    fn state() -> PokerState {
+        let cards = parse_cards("AsKs QhQd 7c7d 2h3h4h").unwrap();
+        PokerState {
+            pot: 6,
+            pots: vec![],
+            player_stacks: HashMap::from([(0, 198), (1, 198), (2, 198)]),
+            player_contributions: HashMap::from([(0, 2), (1, 2), (2, 2)]),
+            community_cards: cards[6..9].to_vec(),
+            player_cards: HashMap::from([
+                (0, (cards[0], cards[1])),
+                (1, (cards[2], cards[3])),
+                (2, (cards[4], cards[5])),
+            ]),
+            last_action: HashMap::new(),
+            required_call: 0,
+            history: cards[..6]
+                .chunks(2)
+                .enumerate()
+                .map(|(player, cards)| HistoryEntry {
+                    street: Street::Preflop,
+                    event: HandEvent::HoleCards {
+                        player,
+                        cards: (cards[0], cards[1]),
+                    },
+                })
+                .collect(),
+            button: 2,
+            positions: HashMap::from([
+                (0, Position::SmallBlind),
+                (1, Position::BigBlind),
+                (2, Position::Button),
+            ]),
+        }
+    }
+
+    #[test]
+    fn observation_holds_only_own_hole_cards() {
+        let observation = state().observation(1);
+        let queens: Vec<Card> = parse_cards("QhQd").unwrap();
+        assert_eq!(observation.player, 1);
+        assert_eq!(observation.hole_cards, (queens[0], queens[1]));
+        assert_eq!(observation.community_cards, parse_cards("2h3h4h").unwrap());
+        assert_eq!(observation.pot, 6);
+        assert_eq!(observation.player_stacks[&2], 198);
+    }
+
+    #[test]
+    fn observation_history_hides_other_hole_cards() {
+        let observation = state().observation(1);
+        let players: Vec<usize> = observation
+            .history
+            .iter()
+            .filter_map(|entry| match entry.event {
+                HandEvent::HoleCards { player, .. } => Some(player),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(players, vec![1]);
+    }
+}
+
+#[cfg(test)]
+mod history_tests {
+    use super::*;
+    use poker::game::GamePlayer;
+
+    fn history_of_passive_hand() -> Vec<HistoryEntry> {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive, &passive];
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.seed(3);
+        engine.play_hand().unwrap();
+        engine.state().history.clone()
+    }
+
+    #[test]
+    fn history_posts_blinds_before_dealing() {
+        let history = history_of_passive_hand();
+        let events: Vec<&HandEvent> = history.iter().take(5).map(|entry| &entry.event).collect();
+        assert!(matches!(events[0], HandEvent::PostBlind { player: 0, .. }));
+        assert!(matches!(events[1], HandEvent::PostBlind { player: 1, .. }));
+        assert!(events[2..]
+            .iter()
+            .all(|event| matches!(event, HandEvent::HoleCards { .. })));
+    }
+
+    #[test]
+    fn history_records_preflop_in_order() {
+        let history = history_of_passive_hand();
+        let preflop: Vec<&HandEvent> = history
+            .iter()
+            .filter(|entry| entry.street == Street::Preflop)
+            .map(|entry| &entry.event)
+            .filter(|event| !matches!(event, HandEvent::HoleCards { .. }))
+            .collect();
+        assert_eq!(
+            preflop,
+            vec![
+                &HandEvent::PostBlind {
+                    player: 0,
+                    blind: Blind::Small,
+                    amount: 1,
+                },
+                &HandEvent::PostBlind {
+                    player: 1,
+                    blind: Blind::Big,
+                    amount: 2,
+                },
+                &HandEvent::Action {
+                    player: 2,
+                    action: PokerAction::Call,
+                    amount: 2,
+                    all_in: false,
+                },
+                &HandEvent::Action {
+                    player: 0,
+                    action: PokerAction::Call,
+                    amount: 1,
+                    all_in: false,
+                },
+                &HandEvent::Action {
+                    player: 1,
+                    action: PokerAction::Check,
+                    amount: 0,
+                    all_in: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn history_groups_cards_dealt_by_street() {
+        let history = history_of_passive_hand();
+        let hole_cards = history
+            .iter()
+            .filter(|entry| matches!(entry.event, HandEvent::HoleCards { .. }))
+            .count();
+        assert_eq!(hole_cards, 3);
+
+        let board: Vec<(Street, usize)> = history
+            .iter()
+            .filter_map(|entry| match &entry.event {
+                HandEvent::CommunityCards { cards } => Some((entry.street, cards.len())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            board,
+            vec![(Street::Flop, 3), (Street::Turn, 1), (Street::River, 1)]
+        );
+    }
+
+    #[test]
+    fn history_records_pot_winnings() {
+        let history = history_of_passive_hand();
+        let won: u64 = history
+            .iter()
+            .filter_map(|entry| match entry.event {
+                HandEvent::WinsPot { amount, .. } => Some(amount),
+                _ => None,
+            })
+            .sum();
+        assert_eq!(won, 6);
+    }
+}
+
+#[cfg(test)]
+mod betting_round_tests {
+    use super::*;
+    use poker::game::GamePlayer;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    /// Plays a list of actions for each player, then checks or folds once it runs out
+    struct ScriptedPlayer {
+        scripts: RefCell<HashMap<usize, VecDeque<PokerAction>>>,
+        /// The legal actions offered at each decision, in order
+        offered: RefCell<Vec<(usize, LegalActions)>>,
+    }
+
+    impl ScriptedPlayer {
+        fn new(scripts: Vec<(usize, Vec<PokerAction>)>) -> Self {
+            ScriptedPlayer {
+                scripts: RefCell::new(
+                    scripts
+                        .into_iter()
+                        .map(|(player, script)| (player, script.into()))
+                        .collect(),
+                ),
+                offered: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl GamePlayer<PlayerObservation, PokerAction, LegalActions> for ScriptedPlayer {
+        fn get_action(&self, state: &PlayerObservation, actions: &LegalActions) -> PokerAction {
+            self.offered
+                .borrow_mut()
+                .push((state.player, actions.clone()));
+            let scripted = self
+                .scripts
+                .borrow_mut()
+                .get_mut(&state.player)
+                .and_then(|script| script.pop_front());
+            match scripted {
+                Some(action) => action,
+                None if actions.can_check => PokerAction::Check,
+                None => PokerAction::Fold,
+            }
+        }
+    }
+
+    /// Plays one hand from the default table, returning the final stacks and the history
+    fn play_scripted(
+        num_players: usize,
+        scripts: Vec<(usize, Vec<PokerAction>)>,
+    ) -> (Vec<u64>, Vec<HistoryEntry>) {
+        let scripted = ScriptedPlayer::new(scripts);
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            (0..num_players).map(|_| &scripted as _).collect();
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.seed(11);
+        engine.play_hand().unwrap();
+        assert!(engine.illegal_actions().is_empty());
+        let stacks = (0..num_players)
+            .map(|player| engine.state().player_stacks[&player])
+            .collect();
+        (stacks, engine.state().history.clone())
+    }
+
+    fn actions_on(history: &[HistoryEntry], street: Street) -> Vec<(usize, PokerAction)> {
+        history
+            .iter()
+            .filter(|entry| entry.street == street)
+            .filter_map(|entry| match &entry.event {
+                HandEvent::Action { player, action, .. } => Some((*player, action.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn streets_dealt(history: &[HistoryEntry]) -> usize {
+        history
+            .iter()
+            .filter(|entry| matches!(entry.event, HandEvent::CommunityCards { .. }))
+            .count()
+    }
+
+    #[test]
+    fn checks_around_give_everyone_one_action_per_street() {
+        for num_players in 2..=10 {
+            // Everyone limps and the big blind checks their option
+            let scripts = (0..num_players)
+                .filter(|player| *player != 1)
+                .map(|player| (player, vec![PokerAction::Call]))
+                .collect();
+            let (stacks, history) = play_scripted(num_players, scripts);
+            for street in [Street::Preflop, Street::Flop, Street::Turn, Street::River] {
+                assert_eq!(actions_on(&history, street).len(), num_players);
+            }
+            assert_eq!(actions_on(&history, Street::Preflop)[num_players - 1].0, 1);
+            assert_eq!(stacks.iter().sum::<u64>(), 200 * num_players as u64);
+        }
+    }
+
+    #[test]
+    fn folds_to_the_big_blind_end_the_hand() {
+        for num_players in 2..=10 {
+            let (stacks, history) = play_scripted(num_players, vec![]);
+            // Everyone but the big blind folds, and the big blind is never asked to act
+            assert_eq!(actions_on(&history, Street::Preflop).len(), num_players - 1);
+            assert_eq!(streets_dealt(&history), 0);
+            assert_eq!(stacks[0], 199);
+            assert_eq!(stacks[1], 201);
+            assert_eq!(stacks.iter().sum::<u64>(), 200 * num_players as u64);
+        }
+    }
+
+    #[test]
+    fn big_blind_can_raise_limpers() {
+        for num_players in 2..=10 {
+            let scripts = (0..num_players)
+                .map(|player| {
+                    if player == 1 {
+                        (player, vec![PokerAction::Raise(6)])
+                    } else {
+                        (player, vec![PokerAction::Call, PokerAction::Call])
+                    }
+                })
+                .collect();
+            let (stacks, history) = play_scripted(num_players, scripts);
+            let preflop = actions_on(&history, Street::Preflop);
+            // Everyone limps, the big blind raises and everyone calls again
+            assert_eq!(preflop.len(), 2 * num_players - 1);
+            assert_eq!(preflop[num_players - 1], (1, PokerAction::Raise(6)));
+            assert_eq!(streets_dealt(&history), 3);
+            assert_eq!(stacks.iter().sum::<u64>(), 200 * num_players as u64);
+        }
+    }
+
+    #[test]
+    fn re_raises_reopen_the_action() {
+        // UTG raises, the small blind re-raises, the big blind folds and UTG calls
+        let (stacks, history) = play_scripted(
+            3,
+            vec![
+                (2, vec![PokerAction::Raise(4), PokerAction::Call]),
+                (0, vec![PokerAction::Raise(12)]),
+            ],
+        );
+        assert_eq!(
+            actions_on(&history, Street::Preflop),
+            vec![
+                (2, PokerAction::Raise(4)),
+                (0, PokerAction::Raise(12)),
+                (1, PokerAction::Fold),
+                (2, PokerAction::Call),
+            ]
+        );
+        assert_eq!(actions_on(&history, Street::Flop).len(), 2);
+        assert_eq!(stacks[1], 198);
+        assert_eq!(stacks.iter().sum::<u64>(), 600);
+    }
+
+    #[test]
+    fn fold_to_a_flop_bet_ends_the_hand_without_showdown() {
+        for num_players in 2..=10 {
+            let mut scripts: Vec<(usize, Vec<PokerAction>)> = (0..num_players)
+                .map(|player| {
+                    if player == 1 {
+                        (player, vec![PokerAction::Check])
+                    } else {
+                        (player, vec![PokerAction::Call])
+                    }
+                })
+                .collect();
+            // The first player to act on the flop bets and everyone else folds. Heads-up that
+            // is the big blind, otherwise the small blind.
+            let bettor = if num_players == 2 { 1 } else { 0 };
+            scripts[bettor].1.push(PokerAction::Raise(10));
+            let (stacks, history) = play_scripted(num_players, scripts);
+            assert_eq!(streets_dealt(&history), 1);
+            assert_eq!(actions_on(&history, Street::Flop).len(), num_players);
+            assert_eq!(actions_on(&history, Street::Flop)[0].0, bettor);
+            assert_eq!(stacks[bettor], 198 + 2 * num_players as u64);
+            assert_eq!(stacks.iter().sum::<u64>(), 200 * num_players as u64);
+        }
+    }
+
+    #[test]
+    fn all_in_players_are_not_asked_to_act() {
+        // UTG moves all-in, the small blind calls and the big blind folds
+        let (stacks, history) = play_scripted(
+            3,
+            vec![
+                (2, vec![PokerAction::Raise(198)]),
+                (0, vec![PokerAction::Call]),
+            ],
+        );
+        assert_eq!(actions_on(&history, Street::Preflop).len(), 3);
+        for street in [Street::Flop, Street::Turn, Street::River] {
+            assert!(actions_on(&history, street).is_empty());
+        }
+        assert_eq!(streets_dealt(&history), 3);
+        assert_eq!(stacks[1], 198);
+        assert_eq!(stacks.iter().sum::<u64>(), 600);
+    }
+
+    #[test]
+    fn no_betting_once_everyone_is_all_in() {
+        // UTG moves all-in and both blinds call
+        let (_, history) = play_scripted(
+            3,
+            vec![
+                (2, vec![PokerAction::Raise(198)]),
+                (0, vec![PokerAction::Call]),
+                (1, vec![PokerAction::Call]),
+            ],
+        );
+        // Everyone is all-in, so there is no more betting
+        for street in [Street::Flop, Street::Turn, Street::River] {
+            assert!(actions_on(&history, street).is_empty());
+        }
+    }
+
+    /// Plays one hand with the button raising to 10 and the short small blind moving all-in
+    /// for 14, returning the legal actions offered and the final stacks
+    fn play_short_all_in(
+        big_blind_script: Vec<PokerAction>,
+        button_script: Vec<PokerAction>,
+    ) -> (Vec<(usize, LegalActions)>, Vec<u64>) {
+        let scripted = ScriptedPlayer::new(vec![
+            (0, vec![PokerAction::Raise(4)]),
+            (1, big_blind_script),
+            (2, button_script),
+        ]);
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&scripted, &scripted, &scripted];
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.seed(11);
+        engine.set_stack(0, 14);
+        engine.play_hand().unwrap();
+        assert!(engine.illegal_actions().is_empty());
+        let stacks = (0..3)
+            .map(|player| engine.state().player_stacks[&player])
+            .collect();
+        (scripted.offered.into_inner(), stacks)
+    }
+
+    #[test]
+    fn short_all_in_does_not_reopen_raising() {
+        // The button raises by 8 and the small blind's all-in only raises by 4 more
+        let (offered, _) = play_short_all_in(
+            vec![PokerAction::Call],
+            vec![PokerAction::Raise(8), PokerAction::Call],
+        );
+        let preflop: Vec<&(usize, LegalActions)> = offered.iter().take(4).collect();
+        assert_eq!(
+            preflop
+                .iter()
+                .map(|(player, _)| *player)
+                .collect::<Vec<_>>(),
+            vec![2, 0, 1, 2]
+        );
+        // The big blind hadn't acted yet, so may still raise
+        assert!(preflop[2].1.raise_range.is_some());
+        // The button already acted and can only call the extra 4 or fold
+        assert_eq!(preflop[3].1.raise_range, None);
+        assert_eq!(preflop[3].1.call_amount, Some(4));
+        assert!(preflop[3].1.can_fold);
+    }
+
+    #[test]
+    fn full_raise_after_short_all_in_reopens_raising() {
+        // The big blind makes a full raise over the all-in, so the button may raise again
+        let (offered, _) = play_short_all_in(
+            vec![PokerAction::Raise(10)],
+            vec![PokerAction::Raise(8), PokerAction::Fold],
+        );
+        let (player, button) = &offered[3];
+        assert_eq!(*player, 2);
+        assert!(button.raise_range.is_some());
+        assert_eq!(button.call_amount, Some(14));
+    }
+}
+
+#[cfg(test)]
+mod position_tests {
+    use super::*;
+    use poker::game::GamePlayer;
+
+    fn engine<'a>(
+        players: &'a Vec<&'a dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>>,
+    ) -> PokerEngine<'a> {
+        let mut engine = PokerEngine::new(players, TableConfig::default());
+        engine.seed(4);
+        engine
+    }
+
+    fn positions(engine: &PokerEngine) -> Vec<Option<String>> {
+        (0..engine.state().player_stacks.len())
+            .map(|player| {
+                engine
+                    .state()
+                    .positions
+                    .get(&player)
+                    .map(|position| position.to_string())
+            })
+            .collect()
+    }
+
+    fn blinds_posted(engine: &PokerEngine) -> Vec<(usize, Blind)> {
+        engine
+            .state()
+            .history
+            .iter()
+            .filter_map(|entry| match entry.event {
+                HandEvent::PostBlind { player, blind, .. } => Some((player, blind)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn six_handed_table_starts_under_the_gun() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            (0..6).map(|_| &passive as _).collect();
+        let mut engine = engine(&players);
+        engine.play_hand().unwrap();
+        let positions = &engine.state().positions;
+        assert_eq!(positions[&0], Position::SmallBlind);
+        assert_eq!(positions[&1], Position::BigBlind);
+        assert_eq!(positions[&2], Position::UnderTheGun(0));
+        assert_eq!(positions[&3], Position::Hijack);
+        assert_eq!(positions[&4], Position::Cutoff);
+        assert_eq!(positions[&5], Position::Button);
+    }
+
+    #[test]
+    fn positions_are_named_from_the_button() {
+        let passive = PassivePokerPlayer {};
+        let expected = [
+            (3, vec!["SB", "BB", "BTN"]),
+            (4, vec!["SB", "BB", "CO", "BTN"]),
+            (5, vec!["SB", "BB", "HJ", "CO", "BTN"]),
+            (6, vec!["SB", "BB", "UTG", "HJ", "CO", "BTN"]),
+            (7, vec!["SB", "BB", "UTG", "LJ", "HJ", "CO", "BTN"]),
+            (
+                9,
+                vec!["SB", "BB", "UTG", "UTG+1", "UTG+2", "LJ", "HJ", "CO", "BTN"],
+            ),
+        ];
+        for (num_players, names) in expected {
+            let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+                (0..num_players).map(|_| &passive as _).collect();
+            let mut engine = engine(&players);
+            engine.play_hand().unwrap();
+            let names: Vec<Option<String>> =
+                names.into_iter().map(|name| Some(name.into())).collect();
+            assert_eq!(positions(&engine), names);
+            assert_eq!(engine.state().button, num_players - 1);
+        }
+    }
+
+    #[test]
+    fn button_moves_one_seat_each_hand() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive, &passive, &passive];
+        let mut engine = engine(&players);
+        for button in [3, 0, 1, 2, 3] {
+            engine.play_hand().unwrap();
+            assert_eq!(engine.state().button, button);
+            assert_eq!(
+                blinds_posted(&engine),
+                vec![
+                    ((button + 1) % 4, Blind::Small),
+                    ((button + 2) % 4, Blind::Big)
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn heads_up_button_posts_small_blind_and_acts_first_preflop_only() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive];
+        let mut engine = engine(&players);
+        for button in [0, 1, 0] {
+            engine.play_hand().unwrap();
+            let big_blind = 1 - button;
+            assert_eq!(engine.state().button, button);
+            assert_eq!(
+                blinds_posted(&engine),
+                vec![(button, Blind::Small), (big_blind, Blind::Big)]
+            );
+            let mut expected = vec![None, None];
+            expected[button] = Some("BTN".to_string());
+            expected[big_blind] = Some("BB".to_string());
+            assert_eq!(positions(&engine), expected);
+            let first_to_act = |street| {
+                engine
+                    .state()
+                    .street_history(street)
+                    .find_map(|entry| match entry.event {
+                        HandEvent::Action { player, .. } => Some(player),
+                        _ => None,
+                    })
+            };
+            assert_eq!(first_to_act(Street::Preflop), Some(button));
+            assert_eq!(first_to_act(Street::Flop), Some(big_blind));
+            assert_eq!(first_to_act(Street::River), Some(big_blind));
+        }
+    }
+
+    #[test]
+    fn busted_big_blind_leaves_a_dead_small_blind() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive, &passive, &passive];
+        let mut engine = engine(&players);
+        engine.play_hand().unwrap();
+        engine.set_stack(1, 0);
+
+        // The big blind still moves on one player, so nobody posts the small blind
+        engine.play_hand().unwrap();
+        assert_eq!(engine.state().button, 0);
+        assert_eq!(blinds_posted(&engine), vec![(2, Blind::Big)]);
+        assert_eq!(
+            positions(&engine),
+            vec![
+                Some("BTN".into()),
+                None,
+                Some("BB".into()),
+                Some("CO".into())
+            ]
+        );
+
+        // Then the button moves onto the empty seat
+        engine.play_hand().unwrap();
+        assert_eq!(engine.state().button, 1);
+        assert_eq!(
+            blinds_posted(&engine),
+            vec![(2, Blind::Small), (3, Blind::Big)]
+        );
+        assert_eq!(
+            positions(&engine),
+            vec![
+                Some("BTN".into()),
+                None,
+                Some("SB".into()),
+                Some("BB".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn busted_small_blind_leaves_a_dead_button() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive, &passive, &passive];
+        let mut engine = engine(&players);
+        engine.play_hand().unwrap();
+        engine.set_stack(0, 0);
+
+        engine.play_hand().unwrap();
+        assert_eq!(engine.state().button, 0);
+        assert_eq!(
+            blinds_posted(&engine),
+            vec![(1, Blind::Small), (2, Blind::Big)]
+        );
+        assert_eq!(
+            positions(&engine),
+            vec![
+                None,
+                Some("SB".into()),
+                Some("BB".into()),
+                Some("BTN".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn players_see_the_button_and_positions() {
+        let state = PokerState {
+            pot: 0,
+            pots: vec![],
+            player_stacks: HashMap::new(),
+            player_contributions: HashMap::new(),
+            community_cards: vec![],
+            player_cards: HashMap::from([(1, ("As".parse().unwrap(), "Kd".parse().unwrap()))]),
+            last_action: HashMap::new(),
+            required_call: 0,
+            history: vec![],
+            button: 0,
+            positions: HashMap::from([(0, Position::Button), (1, Position::BigBlind)]),
+        };
+        let observation = state.observation(1);
+        assert_eq!(observation.button, 0);
+        assert_eq!(observation.positions[&1], Position::BigBlind);
+    }
+}
+
+#[cfg(test)]
+mod observer_tests {
+    use super::*;
+    use poker::card::Card;
+    use poker::game::GamePlayer;
+    use poker::hand_utils::HandRank;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: RefCell<Vec<String>>,
+    }
+
+    impl RecordingObserver {
+        fn push(&self, event: String) {
+            self.events.borrow_mut().push(event);
+        }
+    }
+
+    impl PokerObserver for RecordingObserver {
+        fn hand_started(&self, state: &PokerState) {
+            self.push(format!("started {}", state.button));
+        }
+
+        fn cards_dealt(&self, player: usize, _cards: (Card, Card)) {
+            self.push(format!("dealt {}", player));
+        }
+
+        fn chips_posted(&self, player: usize, amount: u64, blind: Option<Blind>) {
+            self.push(format!("posted {} {} {:?}", player, amount, blind));
+        }
+
+        fn action_taken(
+            &self,
+            player: usize,
+            action: &PokerAction,
+            amount: u64,
+            _state: &PokerState,
+        ) {
+            self.push(format!("action {} {:?} {}", player, action, amount));
+        }
+
+        fn street_dealt(&self, street: Street, cards: &[Card], _state: &PokerState) {
+            self.push(format!("street {:?} {}", street, cards.len()));
+        }
+
+        fn showdown(&self, hands: &HashMap<usize, HandRank>, _state: &PokerState) {
+            self.push(format!("showdown {}", hands.len()));
+        }
+
+        fn pot_awarded(&self, player: usize, pot: usize, _amount: u64) {
+            self.push(format!("awarded {} {}", player, pot));
+        }
+
+        fn hand_finished(&self, _state: &PokerState) {
+            self.push("finished".to_string());
+        }
+
+        fn player_busted(&self, player: usize) {
+            self.push(format!("busted {}", player));
+        }
+    }
+
+    #[test]
+    fn events_follow_the_hand() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive];
+        let observer = RecordingObserver::default();
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.seed(1);
+        engine.add_observer(&observer);
+        engine.play_hand().unwrap();
+
+        let events = observer.events.borrow();
+        assert_eq!(
+            events[..9],
+            [
+                "started 0",
+                "posted 0 1 Some(Small)",
+                "posted 1 2 Some(Big)",
+                "dealt 1",
+                "dealt 0",
+                "action 0 Call 1",
+                "action 1 Check 0",
+                "street Flop 3",
+                "action 1 Check 0",
+            ]
+        );
+        let streets: Vec<&String> = events
+            .iter()
+            .filter(|event| event.starts_with("street"))
+            .collect();
+        assert_eq!(
+            streets,
+            ["street Flop 3", "street Turn 1", "street River 1"]
+        );
+        let showdown = events.iter().position(|e| e == "showdown 2").unwrap();
+        let awarded = events
+            .iter()
+            .position(|e| e.starts_with("awarded"))
+            .unwrap();
+        assert!(showdown < awarded);
+        assert_eq!(events.last().unwrap(), "finished");
+    }
+
+    #[test]
+    fn observers_see_players_bust() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive, &passive];
+        let mut busts = 0;
+        for seed in 0..10 {
+            let observer = RecordingObserver::default();
+            let mut engine = PokerEngine::new(&players, TableConfig::default());
+            engine.seed(seed);
+            engine.set_stack(2, 2);
+            engine.add_observer(&observer);
+            engine.play_hand().unwrap();
+
+            let busted: Vec<String> = (0..3)
+                .filter(|player| engine.state().player_stacks[player] == 0)
+                .map(|player| format!("busted {}", player))
+                .collect();
+            let events = observer.events.borrow();
+            let reported: Vec<String> = events
+                .iter()
+                .filter(|event| event.starts_with("busted"))
+                .cloned()
+                .collect();
+            assert_eq!(reported, busted);
+            busts += busted.len();
+        }
+        assert!(busts > 0);
+    }
+
+    #[test]
+    fn console_observer_leaves_the_hand_unchanged() {
+        let passive = PassivePokerPlayer {};
+        let players: Vec<&dyn GamePlayer<PlayerObservation, PokerAction, LegalActions>> =
+            vec![&passive, &passive];
+        let console = ConsoleObserver {};
+        let mut engine = PokerEngine::new(&players, TableConfig::default());
+        engine.add_observer(&console);
+        engine.play_hand().unwrap();
+        let total: u64 = engine.state().player_stacks.values().sum();
+        assert_eq!(total, 2 * TableConfig::default().starting_stack);
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::hand;
+use poker::card::{parse_cards, CardSet, Rank};
+use poker::range::*;
+
+#[cfg(test)]
+mod range_tests {
+    use super::*;
+
+    #[test]
+    fn hand_classes_expand_to_their_combos() {
+        assert_eq!(parse_range("QQ").unwrap().len(), 6);
+        assert_eq!(parse_range("AKs").unwrap().len(), 4);
+        assert_eq!(parse_range("AKo").unwrap().len(), 12);
+        assert_eq!(parse_range("AK").unwrap().len(), 16);
+        assert_eq!(parse_range("KA").unwrap(), parse_range("AK").unwrap());
+        assert_eq!(parse_range("AhKh").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn plus_and_dash_spans() {
+        // 13 pairs, 12 suited aces and 3 offsuit kings
+        let range = parse_range("22+, A2s+, KTo+").unwrap();
+        assert_eq!(range.len(), 13 * 6 + 12 * 4 + 3 * 12);
+        assert!(range.contains(hand("2c2d")));
+        assert!(range.contains(hand("AsKs")));
+        assert!(range.contains(hand("KhJd")));
+        assert!(!range.contains(hand("AsKd")));
+        assert!(!range.contains(hand("Kh9d")));
+
+        assert_eq!(parse_range("22-55").unwrap().len(), 4 * 6);
+        assert_eq!(
+            parse_range("A5s-A2s").unwrap(),
+            parse_range("A2s-A5s").unwrap()
+        );
+        assert_eq!(parse_range("A2s-A5s").unwrap().len(), 4 * 4);
+    }
+
+    #[test]
+    fn weights_apply_to_their_entry() {
+        let range = parse_range("QJs, 72o, AKs:0.5").unwrap();
+        assert_eq!(range.len(), 4 + 12 + 4);
+        assert_eq!(range.weight(hand("AsKs")), 0.5);
+        assert_eq!(range.weight(hand("KsAs")), 0.5);
+        assert_eq!(range.weight(hand("QhJh")), 1.0);
+        assert_eq!(range.weight(hand("7c2d")), 1.0);
+        assert_eq!(range.weight(hand("7c2c")), 0.0);
+        assert_eq!(range.total_weight(), 18.0);
+
+        // A later entry overrides an earlier one, and a weight of 0 removes the combos
+        let range = parse_range("AA, AsAh:0.25, AdAc:0").unwrap();
+        assert_eq!(range.len(), 5);
+        assert_eq!(range.weight(hand("AhAs")), 0.25);
+    }
+
+    #[test]
+    fn combos_list_the_higher_card_first() {
+        let combos = parse_range("K2s").unwrap().combos();
+        assert_eq!(combos.len(), 4);
+        for combo in combos {
+            assert_eq!(combo.cards.0.rank, Rank::King);
+            assert_eq!(combo.weight, 1.0);
+        }
+    }
+
+    #[test]
+    fn known_cards_remove_combos() {
+        let range: Range = "AA, KK, AKs".parse().unwrap();
+        let board = CardSet::from(&parse_cards("As 7d 2c").unwrap());
+        let remaining = range.without(&board);
+        // Three aces leave 3 pairs of aces and 3 suited AK
+        assert_eq!(remaining.len(), 3 + 6 + 3);
+        assert!(!remaining.contains(hand("AsAh")));
+        assert!(remaining.contains(hand("KsKh")));
+    }
+
+    #[test]
+    fn invalid_notation_is_rejected() {
+        assert_eq!(
+            parse_range("AAs"),
+            Err(ParseRangeError::InvalidEntry("AAs".to_string()))
+        );
+        assert_eq!(
+            parse_range("AK2"),
+            Err(ParseRangeError::InvalidEntry("AK2".to_string()))
+        );
+        assert_eq!(
+            parse_range("22-A5s"),
+            Err(ParseRangeError::InvalidEntry("22-A5s".to_string()))
+        );
+        assert_eq!(
+            parse_range("AKs:2"),
+            Err(ParseRangeError::InvalidWeight("2".to_string()))
+        );
+        assert!(matches!(
+            parse_range("XK"),
+            Err(ParseRangeError::InvalidCards(_))
+        ));
+        assert_eq!(parse_range(" , ").unwrap(), Range::new());
+    }
+}